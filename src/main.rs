@@ -2,36 +2,227 @@
 // This game combines a menu interface with a tower defense mechanic where
 // turrets shoot at flocking boids (bird-like entities that move in groups)
 
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 fn main() {
-    App::new()
-        // Configure the main window with title and resolution
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Game Menu".into(),
-                resolution: (1920., 1080.).into(),
+    // `--headless` runs the simulation without a window or rendering
+    let headless = std::env::args().any(|arg| arg == "--headless");
+    // `--sprites` draws boids with a textured sprite instead of a flat triangle
+    let sprites = std::env::args().any(|arg| arg == "--sprites");
+    // `--record-frames N` sets how many frames the F9 recorder keeps
+    let record_frames = std::env::args()
+        .skip_while(|arg| arg != "--record-frames")
+        .nth(1)
+        .and_then(|frames| frames.parse().ok());
+    // `--seed N` replays a run; without it every run uses `GameRng::DEFAULT_SEED`
+    let seed = std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(GameRng::DEFAULT_SEED);
+    // `--sweep PATH` runs the flocking parameter sweep under that seed and writes it to PATH as CSV
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--sweep").nth(1) {
+        match std::fs::write(&path, run_sweep(&SWEEP_COHESION, &SWEEP_SEPARATION, SWEEP_TICKS, seed)) {
+            Ok(()) => println!("Saved the parameter sweep to {path}"),
+            Err(error) => eprintln!("Cannot save {path}: {error}"),
+        }
+        return;
+    }
+    
+    let mut app = App::new();
+    if headless {
+        add_headless_mode(&mut app);
+    } else {
+        app
+            // Configure the main window with title and resolution
+            .add_plugins(DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Game Menu".into(),
+                    resolution: (1920., 1080.).into(),
+                    ..default()
+                }),
                 ..default()
-            }),
-            ..default()
-        }))
-        // Set background color to dark gray
-        .insert_resource(ClearColor(Color::srgb(0.15, 0.15, 0.15)))
-        // Initialize all game systems on startup
-        .add_systems(Startup, (setup_camera, setup_menu, setup_boids, setup_turrets))
-        // Systems that run every frame
+            }))
+            .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
+            // Set background color to dark gray
+            .insert_resource(ClearColor(Color::srgb(0.15, 0.15, 0.15)))
+            .init_resource::<KillFeed>()
+            .init_resource::<LaserSettings>()
+            .init_resource::<VisualSettings>()
+            .insert_resource(if sprites { RenderStyle::Sprite } else { RenderStyle::Triangle })
+            .init_resource::<VisualAssets>()  // Reads the boid size from `VisualSettings`
+            .init_resource::<SelectionDrag>()
+            .init_resource::<Trails>()
+            .init_resource::<SoundAssets>()
+            .init_resource::<AudioSettings>()
+            .init_resource::<SoundThrottle>()
+            .init_resource::<BuildMode>()
+            .init_resource::<TimeScale>()
+            .init_resource::<Heatmap>()
+            .init_resource::<MinimapRefresh>()
+            .init_resource::<Tutorial>()
+            .insert_resource(record_frames.map_or_else(Recorder::default, |max_frames| Recorder { max_frames, ..default() }))
+            // Track the window size before anything is spawned, then at the start of every frame
+            .add_systems(PreStartup, update_world_bounds)
+            .add_systems(PreUpdate, update_world_bounds)
+            // Camera, UI and purely visual setup
+            .add_systems(Startup, (setup_camera, setup_menu, setup_kill_feed, setup_protect_zone, setup_selection_summary, setup_currency_hud, setup_score_hud, setup_wave_text, setup_diagnostics_overlay, setup_goal_zone, setup_time_scale_hud, setup_minimap, setup_build_menu))
+            // Input handling and rendering; the simulation never depends on these
+            .add_systems(Update, (
+                apply_letterbox,      // Fit the camera viewport to the locked aspect ratio
+                button_system,        // Handle menu button interactions
+                formation_input,      // Form shapes on number keys, release on 0
+                assign_image_formation,  // Form the image shape once it has loaded
+                cycle_integrator,     // Switch boid integration scheme on I
+                update_boid_lod,      // Flag distant boids as low-detail when zoomed out
+                rebuild_boid_mesh,    // Apply boid size changes to the shared mesh
+                draw_boids,           // Render boids with proper orientation and colors
+                update_health_bars,   // Size health bars and hide them at full health
+                attach_turret_visuals,  // Give new turrets and obstacles their meshes
+                update_kill_feed,     // Fade out and expire kill feed lines
+                update_blasts,        // Fade out artillery blast markers
+                capture_screenshot,   // Freeze and save a screenshot on F12
+                select_turrets,       // Click or drag a box to select turrets
+                batch_turret_commands,  // Retarget or sell every selected turret at once
+                update_selection_summary,  // Combined stats for the selection
+                toggle_leader_path,   // Start or stop the choreographed route on L
+                draw_leader_path,     // Show the route and its anchor while active
+                place_gravity_well,   // Right-click to add or remove gravity wells
+            ))
+            .add_systems(Update, (
+                update_currency_hud,  // Show the current credits
+                update_score_hud,     // Show the current score
+                show_hovered_turret_range,  // Range disc around the turret under the cursor
+                update_wave_text,     // Count down to the next wave
+                update_settings_readouts,  // Show flock settings in the settings panel
+                update_diagnostics_overlay,  // FPS and boid count, toggled with F3
+                place_turret.before(select_turrets),  // Click empty ground to build a turret
+                toggle_pause,         // Pause and resume on Escape or Space
+                restart_on_key,       // Start the game over on R
+                cycle_weight_profile, // Compare flocking weight presets on F4
+                cycle_boundary_mode,  // Switch between steer, wrap and bounce edges with B
+                toggle_kind_flocking, // Split the flock by boid kind on K
+                toggle_overlap_resolution,  // Hard anti-overlap pass on O
+                update_cursor_force.before(update_boids),  // Ctrl + hold mouse to attract or repel boids
+                // After the shots are fired and the boids have moved, so a new flash
+                // spans the right length on the frame it appears
+                update_lasers
+                    .after(apply_laser_damage)
+                    .after(update_boids)
+                    .run_if(in_state(GameState::Running)),  // Fade out and track laser flashes
+                update_goal_zone_visual,  // Keep the goal strip on the playfield edge
+                update_ambient_boids.run_if(in_state(GameState::Menu)),  // Drift the menu flock
+                camera_control,       // Pan with the arrow keys, zoom with the mouse wheel
+                update_particles,     // Fly out and fade death bursts
+                update_trails.run_if(in_state(GameState::Running)),  // Leave fading dots behind each boid
+            ))
+            .add_systems(Update, (
+                toggle_build_mode,    // Enter or leave build mode on E
+                move_build_ghost.after(toggle_build_mode),  // Ghost turret follows the cursor
+                update_build_ghost.after(move_build_ghost),  // Tinted by whether it could be built there
+                update_kill_labels,   // Show each turret's kill count above it
+                tint_hot_barrels,     // Redden barrels as they heat up
+                adjust_time_scale,    // Slow down or speed up the simulation with - and =
+                apply_time_scale.after(adjust_time_scale).after(toggle_build_mode),  // Combine time scale and build mode slow-down
+                update_time_scale_hud,  // Show the time scale while it isn't 1x
+                update_heatmap,       // Boid density overlay, toggled with H
+                minimap_update,       // Boid and turret positions in the corner panel
+                trigger_panic.before(update_boids),  // Scatter the flock on P
+                nudge_boids_into_bounds,  // Keep boids on screen when the playfield shrinks
+                record_boid_states.after(resolve_boid_overlap).run_if(in_state(GameState::Running)),  // Buffer this frame's boids
+                dump_recording,       // Write the buffered frames to CSV on F9
+                advance_tutorial.after(toggle_build_mode),  // Move the tutorial on as the player follows it
+                update_tutorial_panel.after(advance_tutorial).after(place_turret),  // Show the current step's callout
+                update_build_menu.before(place_turret),  // Turret kinds to build, locked ones grayed out
+            ))
+            .add_systems(OnEnter(GameState::Paused), show_pause_overlay)
+            .add_systems(OnExit(GameState::Paused), hide_pause_overlay)
+            .add_systems(OnEnter(GameState::GameOver), show_game_over_overlay)
+            // Calm flock behind the menu, swapped for the real one when the game starts
+            .add_systems(OnEnter(GameState::Menu), (reset_game, spawn_ambient_boids, stop_tutorial))
+            .add_systems(OnExit(GameState::Menu), (despawn_ambient_boids, setup_boids, setup_turrets, start_tutorial))
+            .add_systems(OnExit(GameState::GameOver), hide_game_over_overlay)
+            .add_systems(OnEnter(GameState::Victory), show_victory_overlay)
+            .add_systems(OnExit(GameState::Victory), hide_victory_overlay)
+            .add_observer(spawn_laser_flash)
+            .add_observer(push_kill_feed)
+            .add_observer(push_wave_feed)
+            .add_observer(spawn_blast)
+            .add_observer(spawn_death_burst)
+            .add_observer(play_laser_sound)
+            .add_observer(play_death_sound);
+    }
+    
+    add_simulation(&mut app, headless, seed);
+    app.run();
+}
+
+/// Add the simulation shared by windowed and headless runs
+fn add_simulation(app: &mut App, headless: bool, seed: u64) {
+    app.insert_state(if headless { GameState::Running } else { GameState::Menu })
+        .init_resource::<NextBoidId>()
+        .init_resource::<ProtectZone>()
+        .init_resource::<GoalZone>()
+        .init_resource::<Curiosity>()
+        .init_resource::<Formation>()
+        .init_resource::<Integrator>()
+        .init_resource::<BoundaryMode>()
+        .init_resource::<AspectLock>()
+        .init_resource::<Resistances>()
+        .init_resource::<LeaderPath>()
+        .init_resource::<CursorForce>()
+        .init_resource::<Stamina>()
+        .init_resource::<SpatialGrid>()
+        .init_resource::<BoidSettings>()
+        .init_resource::<Currency>()
+        .init_resource::<Score>()
+        .init_resource::<WaveState>()
+        .init_resource::<Difficulty>()
+        .init_resource::<FlockClusters>()
+        .init_resource::<WorldBounds>()
+        .init_resource::<PanicTimer>()
+        .init_resource::<BuildLimits>()
+        .init_resource::<TechProgress>()
+        .insert_resource(GameRng::new(seed))
+        // Spawn the initial flock and defenses
+        .add_systems(PreStartup, load_settings)  // Before setup_boids reads the wave size
+        .add_systems(Startup, setup_obstacles)
+        // Systems that run every frame while the game isn't paused
         .add_systems(Update, (
-            button_system,        // Handle menu button interactions
+            decay_curiosity,      // Fade out boid interest in new turrets
+            advance_leader_path,  // Move the scripted anchor leaders follow
+            move_leader.before(update_boids),  // Circle the leader boid others follow
             update_boids,         // Update boid movement and flocking behavior
-            draw_boids,          // Render boids with proper orientation and colors
-            update_turrets,      // Turret targeting and laser creation
-            update_lasers,       // Update laser beam positions and lengths
-            apply_laser_damage,  // Apply damage to targeted boids
-            respawn_boids,       // Maintain boid population
-        ))
-        .run();
+            update_turrets.after(update_boids),  // Turret targeting, from the grid `update_boids` just filled
+            apply_laser_damage,   // Apply damage to targeted boids
+            update_projectiles.after(apply_laser_damage),  // Steer homing rounds and hit what they touch
+            respawn_boids,        // Send the next wave once the last is gone
+            detect_zone_leaks,    // Count boids reaching the protected zone
+            fire_artillery,       // Shell dense boid clusters
+            update_predator,      // Chase and eat the nearest boid
+            tick_survival_score,  // Slowly add points for staying alive
+            ramp_difficulty,      // Speed boids up the longer the run lasts
+            tick_panic.before(update_boids),  // Run down the panic countdown
+            count_flock_clusters.after(resolve_boid_overlap),  // Periodically count separate flocks
+            resolve_boid_overlap.after(update_boids),  // Push apart boids that overlap after moving
+            detect_goal_zone,     // Lose the game when a boid gets through
+            unlock_turret_kinds,  // Open up higher turret tiers as waves and score climb
+        ).run_if(in_state(GameState::Running)))
+        // Clear the board and start again after Retry or R
+        .add_systems(OnEnter(GameState::Restarting), (reset_game, setup_boids, setup_turrets, finish_restart).chain())
+        // Print the per-turret summary when the game closes
+        .add_systems(Last, (log_turret_summary, save_settings))
+        // Log boid lifecycle events (mods can add their own observers the same way)
+        .add_observer(log_boid_spawned)
+        .add_observer(log_boid_despawned)
+        .add_observer(spark_curiosity)
+        .add_observer(earn_bounty);
 }
 
 // ===== COMPONENT DEFINITIONS =====
@@ -45,10 +236,103 @@ struct MainMenu;
 struct Boid {
     velocity: Vec2,              // Current movement direction and speed
     acceleration: Vec2,          // Forces applied this frame
-    health: f32,                // Health from 0.0 to 1.0
+    health: f32,                // Remaining health, from 0.0 up to `max_health`
+    max_health: f32,            // Health at spawn, set by the boid's kind
+    max_speed: f32,             // Top speed at full energy, set by the boid's kind
     damage_flash_timer: Timer,   // Timer for red damage flash effect
+    team: u8,                    // Flock this boid belongs to; only flocks with its own team
+    energy: f32,                 // Stamina from 0.0 to 1.0; sprinting drains it and caps top speed
+    generation: u8,              // Times this boid's line has split; 0 for a freshly spawned boid
+}
+
+/// Species of boid, fixed at spawn; decides its base color, speed and toughness
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum BoidKind {
+    Normal,   // Regular flock member, colored by team
+    Pink,     // A single standout boid
+    Red,      // A handful of marked boids
+    Fast,     // Quick but fragile
+    Tank,     // Slow but takes several times the punishment
+    Splitter, // Breaks into two smaller, faster boids when a turret destroys it
+}
+
+impl BoidKind {
+    /// Pick a kind for an ordinary spawn: mostly normal, with some fast and a few tank boids
+    fn random(rng: &mut impl Rng) -> Self {
+        match rng.random_range(0.0..1.0) {
+            roll if roll < 0.2 => BoidKind::Fast,
+            roll if roll < 0.3 => BoidKind::Tank,
+            roll if roll < 0.4 => BoidKind::Splitter,
+            _ => BoidKind::Normal,
+        }
+    }
+    
+    /// Top speed relative to the flock's `max_speed` setting
+    fn speed_scale(self) -> f32 {
+        match self {
+            BoidKind::Fast => 1.4,
+            BoidKind::Tank => 0.6,
+            _ => 1.0,
+        }
+    }
+    
+    /// Health at spawn (a normal boid has 1.0)
+    fn max_health(self) -> f32 {
+        match self {
+            BoidKind::Fast => 0.5,
+            BoidKind::Tank => 3.0,
+            BoidKind::Splitter => 1.5,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Splits allowed along one line of splitters: the original and its children split,
+/// grandchildren don't
+const MAX_SPLIT_GENERATION: u8 = 2;
+
+/// Spawn the two halves of a splitter destroyed at `position`
+///
+/// The halves are queued, so they don't exist until the killing system finishes and
+/// can't be caught by the same volley or blast.
+fn split_boid(commands: &mut Commands, next_id: &mut NextBoidId, parent: &Boid, position: Vec2) {
+    if parent.generation >= MAX_SPLIT_GENERATION {
+        return;
+    }
+    
+    let spread = 0.5;  // Radians each half veers off the parent's heading
+    for side in [-1.0, 1.0] {
+        let velocity = Vec2::from_angle(spread * side).rotate(parent.velocity) * 1.2;
+        let max_health = parent.max_health * 0.5;
+        let id = next_id.next();
+        let entity = commands.spawn((
+            Boid {
+                velocity,
+                acceleration: Vec2::ZERO,
+                health: max_health,
+                max_health,
+                max_speed: parent.max_speed * 1.2,
+                energy: parent.energy,
+                damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
+                team: parent.team,
+                generation: parent.generation + 1,
+            },
+            BoidKind::Splitter,
+            id,
+            Transform::from_translation((position + velocity.normalize_or_zero() * 4.0).extend(0.0)),
+        )).id();
+        commands.trigger(BoidSpawned { entity, id });
+    }
 }
 
+/// Marker for the health bar floating above a damaged boid
+#[derive(Component)]
+struct HealthBar;
+
+/// Marker for boids far from the camera center while zoomed out, drawn with less detail
+#[derive(Component)]
+struct LowDetail;
+
 /// Marker component for boid visual representations (triangular meshes)
 #[derive(Component)]
 struct BoidVisual;
@@ -56,15 +340,166 @@ struct BoidVisual;
 /// Turret component for defensive structures
 #[derive(Component)]
 struct Turret {
-    target: Option<Entity>,      // Currently targeted boid entity
+    targets: Vec<Entity>,        // Boids currently being tracked, best first
+    max_targets: usize,          // How many boids the turret can track and shoot at once
     range: f32,                  // Maximum targeting range
     cooldown_timer: Timer,       // Delay between target acquisitions
+    targeting: TargetingMode,    // How the next target is chosen
+    spin_up_timer: Timer,        // Damage ramps to full over this after acquiring a target
+    prefer_approaching: bool,    // Rank boids heading away behind every approaching one
+    fire_timer: Timer,           // Time between shots; its duration sets the fire rate
+    shot_damage: f32,            // Health removed by each shot at full spin-up
+    projectile_speed: Option<f32>,  // Shot speed used to lead targets; None for instant beams
+    level: u32,                  // Upgrade level, 1 to `Turret::MAX_LEVEL`
+    aim_angle: f32,              // Direction the barrel points (radians from +X)
+    turn_speed: f32,             // Fastest the barrel can swing (radians per second)
+    on_target: bool,             // Barrel is within `AIM_TOLERANCE` of the primary target
+    rotate_base: bool,           // Turn the whole turret to aim instead of swivelling the barrel on a fixed base
+    heat: f32,                   // Barrel heat, from 0 (cold) up to `Turret::OVERHEAT`
+    heat_per_shot: f32,          // Heat added by each volley
+    cooling_rate: f32,           // Heat shed per second, firing or not
+    overheated: bool,            // Holding fire until heat falls back to `Turret::RESUME_HEAT`
+}
+
+impl Turret {
+    /// Highest level a turret can be upgraded to
+    const MAX_LEVEL: u32 = 3;
+    
+    /// Credits needed to reach the next level
+    fn upgrade_cost(&self) -> u32 {
+        40 * self.level
+    }
+    
+    /// Credits refunded on sale: half of the build price plus every upgrade paid for
+    fn sell_value(&self) -> u32 {
+        let upgrades: u32 = (1..self.level).map(|level| 40 * level).sum();
+        (TURRET_COST + upgrades) / 2
+    }
+    
+    /// Shot damage multiplier from upgrades (+50% per level)
+    fn damage_multiplier(&self) -> f32 {
+        1.0 + 0.5 * (self.level - 1) as f32
+    }
+    
+    /// Raise the level, extending range and shortening the time between shots
+    fn upgrade(&mut self) {
+        self.level += 1;
+        self.range += 50.0;
+        let interval = self.fire_timer.duration().mul_f32(0.8);
+        self.fire_timer.set_duration(interval);
+    }
+    
+    /// Heat at which the turret overheats and stops firing
+    const OVERHEAT: f32 = 1.0;
+    
+    /// Heat an overheated turret must cool to before it fires again
+    const RESUME_HEAT: f32 = 0.4;
+    
+    /// Distance from the turret center to the barrel tip
+    const BARREL_REACH: f32 = 17.0;
+    
+    /// World position of the barrel tip, where shots leave the turret
+    fn muzzle(&self, turret_pos: Vec2) -> Vec2 {
+        turret_pos + Vec2::from_angle(self.aim_angle) * Self::BARREL_REACH
+    }
+}
+
+/// Marker for the barrel child that swivels to aim at the target
+#[derive(Component)]
+struct TurretBarrel;
+
+/// Strategy a turret uses to pick its next target among boids in range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TargetingMode {
+    Closest,       // Nearest boid, weighted towards those heading for the protected zone
+    Threat,        // Boid closest to reaching any turret, weighted by its closing speed
+    LowestHealth,  // Most wounded boid, to finish it off
+    HighestValue,  // Boid worth the most points
+    FirstInZone,   // Boid closest to reaching the protected zone, weighted by its closing speed
+}
+
+impl TargetingMode {
+    /// Every mode, in cycling order
+    const ALL: [TargetingMode; 5] = [
+        TargetingMode::Closest,
+        TargetingMode::Threat,
+        TargetingMode::LowestHealth,
+        TargetingMode::HighestValue,
+        TargetingMode::FirstInZone,
+    ];
+    
+    /// The mode after this one, wrapping around
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+    
+    /// Short name for the selection summary
+    fn label(self) -> &'static str {
+        match self {
+            TargetingMode::Closest => "Closest",
+            TargetingMode::Threat => "Threat",
+            TargetingMode::LowestHealth => "Lowest health",
+            TargetingMode::HighestValue => "Highest value",
+            TargetingMode::FirstInZone => "First in zone",
+        }
+    }
+}
+
+/// Stable, 1-based turret number used in player-facing text ("Turret #2")
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct TurretId(u32);
+
+/// Lifetime performance of a turret over the session
+#[derive(Component, Default, Debug)]
+struct TurretStats {
+    shots_fired: u32,     // Laser shots or artillery shells fired
+    damage_dealt: f32,    // Total health removed from boids
+    kills: u32,           // Boids finished off by this turret
+    credits_earned: u32,  // Bounties paid out for those kills
+}
+
+impl TurretStats {
+    /// One-line summary, e.g. "12 shots, 8.4 damage, 3 kills, 15 credits"
+    fn describe(&self) -> String {
+        format!(
+            "{} shots, {:.1} damage, {} kills, {} credits",
+            self.shots_fired, self.damage_dealt, self.kills, self.credits_earned
+        )
+    }
+}
+
+/// MVP turret: the one with the most kills, ties broken by damage dealt
+fn mvp_turret<'a>(
+    turrets: impl IntoIterator<Item = (&'a TurretId, &'a TurretStats)>,
+) -> Option<(&'a TurretId, &'a TurretStats)> {
+    turrets
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.kills.cmp(&b.kills).then(a.damage_dealt.total_cmp(&b.damage_dealt)))
 }
 
 /// Laser beam component linking beams to their source turrets
 #[derive(Component)]
 struct LaserBeam {
     turret: Entity,              // Which turret owns this laser
+    target: Entity,              // Boid the shot was fired at
+    lifetime: Timer,             // Flash despawns when this runs out
+}
+
+/// Tunables for laser beam rendering
+#[derive(Resource)]
+struct LaserSettings {
+    max_rendered_beams: usize,   // Beams drawn at once; damage is applied regardless
+    flash_duration: f32,         // Seconds each shot's beam stays visible
+}
+
+impl Default for LaserSettings {
+    fn default() -> Self {
+        Self {
+            max_rendered_beams: 64,
+            flash_duration: 0.1,
+        }
+    }
 }
 
 /// Enum defining different menu button types
@@ -75,13 +510,383 @@ enum MenuButton {
     Settings,
     Quit,
     Character,
+    Back,                          // Leave the settings panel
+    Retry,                         // Start over from the pause, game over or victory screen
+    MainMenu,                      // Clear the board and go back to the main menu
+    Adjust(SettingField, f32),     // Step a flock setting by this amount
+    Resolution(usize),             // Resize the window to this entry of `RESOLUTION_PRESETS`
+}
+
+/// Window sizes offered in the settings panel
+const RESOLUTION_PRESETS: [(f32, f32); 3] = [(1280.0, 720.0), (1600.0, 900.0), (1920.0, 1080.0)];
+
+/// Marker for the main menu's button column
+#[derive(Component)]
+struct MainMenuColumn;
+
+/// Marker for the settings panel that replaces the button column
+#[derive(Component)]
+struct SettingsPanel;
+
+/// Flock and audio parameters adjustable from the settings panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingField {
+    PerceptionRadius,
+    MaxSpeed,
+    TargetCount,
+    MasterVolume,  // Shown and stepped in percent
+}
+
+impl SettingField {
+    fn label(self) -> &'static str {
+        match self {
+            SettingField::PerceptionRadius => "Perception",
+            SettingField::MaxSpeed => "Max speed",
+            SettingField::TargetCount => "First wave",
+            SettingField::MasterVolume => "Volume %",
+        }
+    }
+    
+    fn value(self, settings: &BoidSettings, audio: &AudioSettings) -> f32 {
+        match self {
+            SettingField::PerceptionRadius => settings.perception_radius,
+            SettingField::MaxSpeed => settings.max_speed,
+            SettingField::TargetCount => settings.target_count as f32,
+            SettingField::MasterVolume => audio.master_volume * 100.0,
+        }
+    }
+    
+    /// Sane (min, max) for the stored value (volume as a 0-1 fraction, not percent)
+    fn range(self) -> (f32, f32) {
+        match self {
+            SettingField::PerceptionRadius => (20.0, 300.0),
+            SettingField::MaxSpeed => (100.0, 600.0),
+            SettingField::TargetCount => (10.0, 1000.0),
+            SettingField::MasterVolume => (0.0, 1.0),
+        }
+    }
+    
+    /// Step the setting, keeping it within a sane range
+    fn adjust(self, settings: &mut BoidSettings, audio: &mut AudioSettings, step: f32) {
+        let (min, max) = self.range();
+        match self {
+            SettingField::PerceptionRadius => {
+                settings.perception_radius = (settings.perception_radius + step).clamp(min, max);
+            }
+            SettingField::MaxSpeed => {
+                settings.max_speed = (settings.max_speed + step).clamp(min, max);
+            }
+            SettingField::TargetCount => {
+                settings.target_count = (settings.target_count as f32 + step).clamp(min, max) as usize;
+            }
+            SettingField::MasterVolume => {
+                audio.master_volume = (audio.master_volume + step / 100.0).clamp(min, max);
+            }
+        }
+    }
+}
+
+/// Text showing the current value of a setting in the settings panel
+#[derive(Component)]
+struct SettingReadout(SettingField);
+
+/// Stable identifier assigned to each boid at spawn (entity IDs get recycled)
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct BoidId(u32);
+
+/// Counter handing out the next free `BoidId`
+#[derive(Resource, Default)]
+struct NextBoidId(u32);
+
+impl NextBoidId {
+    /// Take the next unused ID
+    fn next(&mut self) -> BoidId {
+        let id = BoidId(self.0);
+        self.0 += 1;
+        id
+    }
+}
+
+/// Seeded random number generator for everything that shapes the simulation
+///
+/// Runs started with the same seed spawn the same boids in the same places, so a
+/// run can be reproduced from its seed. Purely cosmetic effects keep the thread RNG.
+#[derive(Resource)]
+struct GameRng {
+    rng: StdRng,
+    seed: u64,  // Kept so a restart can replay the run from the start
+}
+
+impl GameRng {
+    /// Seed used when none is given on the command line
+    const DEFAULT_SEED: u64 = 402;
+    
+    fn new(seed: u64) -> Self {
+        info!("Random seed: {seed}");
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+        }
+    }
+}
+
+// ===== BOID LIFECYCLE EVENTS =====
+// Triggered globally so observers (`App::add_observer`) can react to boids
+// appearing and dying without touching the core simulation systems.
+
+/// Fired when a boid is spawned, either at startup or by respawning
+///
+/// * `entity` - the new boid entity (its components are queued, not yet applied)
+/// * `id` - the boid's stable `BoidId`
+#[derive(Event, Debug)]
+struct BoidSpawned {
+    entity: Entity,
+    id: BoidId,
+}
+
+/// Fired when a boid is removed from the world
+///
+/// * `entity` - the boid entity (already queued for despawn)
+/// * `id` - the boid's stable `BoidId`
+/// * `kind` - the boid's `BoidKind`
+/// * `cause` - why the boid was removed
+#[derive(Event, Debug)]
+struct BoidDespawned {
+    entity: Entity,
+    id: BoidId,
+    kind: BoidKind,
+    cause: DespawnCause,
+}
+
+/// Fired when the next wave starts streaming in
+///
+/// * `wave` - the wave number
+/// * `size` - how many boids it brings
+#[derive(Event, Debug)]
+struct WaveStarted {
+    wave: u32,
+    size: usize,
+}
+
+/// Fired when a turret is placed on the map
+///
+/// * `position` - world position of the turret base
+#[derive(Event, Debug)]
+struct TurretPlaced {
+    position: Vec2,
+}
+
+/// Fired when a laser turret takes a shot
+///
+/// * `turret` / `target` - shooting turret and the boid it hit
+/// * `from` / `to` - barrel tip and target positions at the moment of the shot
+#[derive(Event, Debug)]
+struct TurretFired {
+    turret: Entity,
+    target: Entity,
+    from: Vec2,
+    to: Vec2,
+}
+
+/// Fired when an artillery shell lands
+///
+/// * `position` - world position of the impact
+/// * `radius` - splash radius of the shell
+#[derive(Event, Debug)]
+struct ArtilleryImpact {
+    position: Vec2,
+    radius: f32,
+}
+
+/// Fired when a turret kills a boid, whatever it fired
+///
+/// * `position` - where the boid died
+/// * `color` - the boid's base color, for effects
+#[derive(Event, Debug)]
+struct BoidKilled {
+    position: Vec2,
+    color: Color,
+}
+
+/// Reason a boid left the simulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DespawnCause {
+    KilledByTurret(TurretId),  // Health depleted by fire from this turret
+    ReachedZone,               // Leaked into the protected zone
+    Eaten,                     // Caught by the predator
+}
+
+// ===== SHARED VISUAL ASSETS =====
+
+/// Mesh and material handles created once and cloned by every system that spawns visuals
+///
+/// Per-frame systems must reuse these rather than calling `meshes.add` /
+/// `materials.add`, which would grow the asset storage every frame.
+#[derive(Resource)]
+struct VisualAssets {
+    boid_mesh: Handle<Mesh>,                     // Forward-pointing triangle
+    turret_base_mesh: Handle<Mesh>,              // Square turret base
+    turret_barrel_mesh: Handle<Mesh>,            // Thin barrel on top of the base
+    artillery_base_mesh: Handle<Mesh>,           // Larger square artillery base
+    laser_mesh: Handle<Mesh>,                    // Unit-length beam, stretched via Transform scale
+    unit_circle_mesh: Handle<Mesh>,              // Radius 1 circle, scaled to size
+    health_bar_mesh: Handle<Mesh>,               // Unit square, scaled to the boid's health
+    turret_material: Handle<ColorMaterial>,      // Dark gray turret parts
+    barrel_heat_materials: Vec<Handle<ColorMaterial>>,  // Barrel tint from cold (turret gray) to overheated red
+    upgraded_turret_materials: Vec<Handle<ColorMaterial>>,  // Brighter bases for levels 2 and up
+    artillery_material: Handle<ColorMaterial>,   // Bronze artillery base
+    laser_material: Handle<ColorMaterial>,       // Semi-transparent red beam
+    projectile_material: Handle<ColorMaterial>,  // Bright orange homing rounds
+    health_bar_material: Handle<ColorMaterial>,  // Solid green
+    obstacle_material: Handle<ColorMaterial>,    // Muted slate
+    range_material: Handle<ColorMaterial>,       // Faint white turret range disc
+    ghost_valid_material: Handle<ColorMaterial>,    // Translucent green build ghost
+    ghost_invalid_material: Handle<ColorMaterial>,  // Translucent red build ghost
+    ambient_boid_material: Handle<ColorMaterial>,  // Muted blue for the menu flock
+    gravity_well_material: Handle<ColorMaterial>,  // Translucent violet well core
+    predator_material: Handle<ColorMaterial>,    // Orange predator triangle
+    leader_material: Handle<ColorMaterial>,      // Gold leader triangle
+    zone_material: Handle<ColorMaterial>,        // Translucent blue protected zone
+    blast_materials: Vec<Handle<ColorMaterial>>, // Orange artillery flash, faintest first
+    boid_texture: Handle<Image>,                 // White arrow, tinted per boid in `RenderStyle::Sprite`
+    heatmap_materials: Vec<Handle<ColorMaterial>>,  // Density heatmap cells, sparsest first
+}
+
+impl FromWorld for VisualAssets {
+    fn from_world(world: &mut World) -> Self {
+        let boid_shape = world.resource::<VisualSettings>().boid_triangle();
+        let boid_texture = world.resource::<AssetServer>().load("sprites/boid.png");
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let boid_mesh = meshes.add(boid_shape);
+        let turret_base_mesh = meshes.add(Rectangle::new(20.0, 20.0));
+        let turret_barrel_mesh = meshes.add(Rectangle::new(6.0, 14.0));
+        let artillery_base_mesh = meshes.add(Rectangle::new(26.0, 26.0));
+        let laser_mesh = meshes.add(Rectangle::new(2.0, 1.0));
+        let unit_circle_mesh = meshes.add(Circle::new(1.0));
+        let health_bar_mesh = meshes.add(Rectangle::new(1.0, 1.0));
+        
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let turret_color = Color::srgb(0.3, 0.3, 0.3);
+        let tint_steps = 8;  // Shades in the barrel heat and blast fade ramps
+        Self {
+            boid_mesh,
+            turret_base_mesh,
+            turret_barrel_mesh,
+            artillery_base_mesh,
+            laser_mesh,
+            unit_circle_mesh,
+            health_bar_mesh,
+            turret_material: materials.add(ColorMaterial::from(turret_color)),
+            barrel_heat_materials: (0..=tint_steps)
+                .map(|step| {
+                    let heat = step as f32 / tint_steps as f32;
+                    materials.add(ColorMaterial::from(turret_color.mix(&Color::srgb(1.0, 0.15, 0.05), heat)))
+                })
+                .collect(),
+            upgraded_turret_materials: vec![
+                materials.add(ColorMaterial::from(Color::srgb(0.5, 0.5, 0.55))),  // Level 2
+                materials.add(ColorMaterial::from(Color::srgb(0.75, 0.75, 0.8))), // Level 3
+            ],
+            artillery_material: materials.add(ColorMaterial::from(Color::srgb(0.45, 0.35, 0.2))),
+            laser_material: materials.add(ColorMaterial::from(Color::srgba(1.0, 0.0, 0.0, 0.7))),
+            projectile_material: materials.add(ColorMaterial::from(Color::srgb(1.0, 0.65, 0.1))),
+            health_bar_material: materials.add(ColorMaterial::from(Color::srgb(0.2, 0.9, 0.2))),
+            obstacle_material: materials.add(ColorMaterial::from(Color::srgb(0.3, 0.33, 0.4))),
+            range_material: materials.add(ColorMaterial::from(Color::srgba(1.0, 1.0, 1.0, 0.08))),
+            ghost_valid_material: materials.add(ColorMaterial::from(Color::srgba(0.2, 0.9, 0.2, 0.5))),
+            ghost_invalid_material: materials.add(ColorMaterial::from(Color::srgba(0.9, 0.2, 0.2, 0.5))),
+            ambient_boid_material: materials.add(ColorMaterial::from(Color::srgba(0.5, 0.6, 0.8, 0.6))),
+            gravity_well_material: materials.add(ColorMaterial::from(Color::srgba(0.6, 0.3, 1.0, 0.5))),
+            predator_material: materials.add(ColorMaterial::from(Color::srgb(1.0, 0.5, 0.0))),
+            leader_material: materials.add(ColorMaterial::from(Color::srgb(1.0, 0.8, 0.3))),
+            zone_material: materials.add(ColorMaterial::from(Color::srgba(0.2, 0.6, 1.0, 0.15))),
+            blast_materials: (1..=tint_steps)
+                .map(|step| {
+                    let alpha = 0.5 * step as f32 / tint_steps as f32;
+                    materials.add(ColorMaterial::from(Color::srgba(1.0, 0.6, 0.1, alpha)))
+                })
+                .collect(),
+            boid_texture,
+            heatmap_materials: vec![
+                materials.add(ColorMaterial::from(Color::srgba(0.2, 0.4, 1.0, 0.15))),  // 1-2 boids
+                materials.add(ColorMaterial::from(Color::srgba(0.2, 0.9, 0.4, 0.2))),   // 3-5
+                materials.add(ColorMaterial::from(Color::srgba(1.0, 0.85, 0.2, 0.25))), // 6-10
+                materials.add(ColorMaterial::from(Color::srgba(1.0, 0.2, 0.1, 0.3))),   // 11 or more
+            ],
+        }
+    }
+}
+
+/// Player-facing visual preferences
+#[derive(Resource)]
+struct VisualSettings {
+    smooth_damage_flash: bool,   // Blend towards red when hit instead of strobing
+    boid_length: f32,            // Tip-to-base length of the boid triangle
+    boid_width: f32,             // Width of the boid triangle's base
+}
+
+impl Default for VisualSettings {
+    fn default() -> Self {
+        Self {
+            smooth_damage_flash: true,
+            boid_length: 8.0,
+            boid_width: 6.0,
+        }
+    }
+}
+
+impl VisualSettings {
+    /// Forward-pointing (+Y) boid triangle, with 5/8 of its length ahead of the origin
+    fn boid_triangle(&self) -> Triangle2d {
+        let tip = self.boid_length * 5.0 / 8.0;
+        let base = tip - self.boid_length;
+        let half_width = self.boid_width / 2.0;
+        Triangle2d::new(
+            Vec2::new(0.0, tip),            // Top point (forward)
+            Vec2::new(-half_width, base),   // Bottom left
+            Vec2::new(half_width, base),    // Bottom right
+        )
+    }
+}
+
+/// How boids are drawn, chosen at launch (`--sprites`)
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+enum RenderStyle {
+    #[default]
+    Triangle,  // Flat mesh triangle with its own color material
+    Sprite,    // Textured arrow sprite, tinted through its color
+}
+
+/// Regenerate the shared boid mesh when the configured size changes
+fn rebuild_boid_mesh(
+    visual_settings: Res<VisualSettings>,
+    visual_assets: Res<VisualAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if visual_settings.is_changed() && !visual_settings.is_added() {
+        // Replacing the asset in place updates every boid sharing the handle
+        meshes.insert(&visual_assets.boid_mesh, Mesh::from(visual_settings.boid_triangle()));
+    }
 }
 
 // ===== SETUP SYSTEMS =====
 
 /// Initialize the 2D camera for the game
-fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
+fn setup_camera(mut commands: Commands, clear_color: Res<ClearColor>) {
+    commands.spawn((
+        Camera2d,
+        Camera {
+            clear_color: ClearColorConfig::Custom(Color::BLACK),  // Letterbox bars
+            ..default()
+        },
+    ));
+    
+    // The playfield itself keeps the regular background color
+    commands.spawn((
+        Sprite::from_color(clear_color.0, Vec2::ONE),
+        Transform::from_xyz(0.0, 0.0, -10.0),  // Behind everything else
+        PlayfieldBackground,
+    ));
 }
 
 /// Create the main menu UI with buttons and title
@@ -102,12 +907,15 @@ fn setup_menu(mut commands: Commands) {
         .with_children(|parent| {
             // Left side menu container
             parent
-                .spawn(Node {
-                    flex_direction: FlexDirection::Column,      // Stack buttons vertically
-                    align_items: AlignItems::FlexStart,         // Align to left
-                    row_gap: Val::Px(20.0),                    // 20px gap between buttons
-                    ..default()
-                })
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,      // Stack buttons vertically
+                        align_items: AlignItems::FlexStart,         // Align to left
+                        row_gap: Val::Px(20.0),                    // 20px gap between buttons
+                        ..default()
+                    },
+                    MainMenuColumn,
+                ))
                 .with_children(|parent| {
                     // Character button (special placement at top)
                     spawn_menu_button(parent, "Character", MenuButton::Character);
@@ -129,6 +937,79 @@ fn setup_menu(mut commands: Commands) {
                     spawn_menu_button(parent, "Settings", MenuButton::Settings);
                     spawn_menu_button(parent, "Quit", MenuButton::Quit);
                 });
+            
+            // Settings panel, hidden until the Settings button swaps it in
+            parent
+                .spawn((
+                    Node {
+                        display: Display::None,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::FlexStart,
+                        row_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    SettingsPanel,
+                ))
+                .with_children(|parent| {
+                    for (field, step) in [
+                        (SettingField::PerceptionRadius, 10.0),
+                        (SettingField::MaxSpeed, 25.0),
+                        (SettingField::TargetCount, 10.0),
+                        (SettingField::MasterVolume, 10.0),
+                    ] {
+                        // One row per setting: readout, then - and + buttons
+                        parent
+                            .spawn(Node {
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(10.0),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(""),
+                                    TextFont {
+                                        font_size: 24.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                    Node {
+                                        width: Val::Px(250.0),
+                                        ..default()
+                                    },
+                                    SettingReadout(field),
+                                ));
+                                spawn_sized_button(parent, "-", MenuButton::Adjust(field, -step), 50.0);
+                                spawn_sized_button(parent, "+", MenuButton::Adjust(field, step), 50.0);
+                            });
+                    }
+                    
+                    // Resolution presets share one row
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new("Resolution"),
+                                TextFont {
+                                    font_size: 24.0,
+                                    ..default()
+                                },
+                                TextColor(Color::WHITE),
+                                Node {
+                                    width: Val::Px(250.0),
+                                    ..default()
+                                },
+                            ));
+                            for (index, (width, height)) in RESOLUTION_PRESETS.iter().enumerate() {
+                                let label = format!("{width}x{height}");
+                                spawn_sized_button(parent, &label, MenuButton::Resolution(index), 150.0);
+                            }
+                        });
+                    spawn_menu_button(parent, "Back", MenuButton::Back);
+                });
 
             // Game title positioned in top right corner
             parent
@@ -151,17 +1032,41 @@ fn setup_menu(mut commands: Commands) {
         });
 }
 
+/// Show the current flock and audio settings next to their +/- buttons
+fn update_settings_readouts(
+    settings: Res<BoidSettings>,
+    audio: Res<AudioSettings>,
+    mut readouts: Query<(&mut Text, &SettingReadout)>,
+) {
+    if !settings.is_changed() && !audio.is_changed() {
+        return;
+    }
+    for (mut text, readout) in &mut readouts {
+        text.0 = format!("{}: {:.0}", readout.0.label(), readout.0.value(&settings, &audio));
+    }
+}
+
 /// Helper function to create individual menu buttons
 fn spawn_menu_button(
     parent: &mut ChildSpawnerCommands,
     text: &str,
     button_type: MenuButton,
+) {
+    spawn_sized_button(parent, text, button_type, 250.0);
+}
+
+/// Menu button with a custom width
+fn spawn_sized_button(
+    parent: &mut ChildSpawnerCommands,
+    text: &str,
+    button_type: MenuButton,
+    width: f32,
 ) {
     parent
         .spawn((
             Button,                                      // Bevy button component
             Node {
-                width: Val::Px(250.0),                  // Fixed width
+                width: Val::Px(width),                  // Fixed width
                 height: Val::Px(50.0),                  // Fixed height
                 justify_content: JustifyContent::Center, // Center text horizontally
                 align_items: AlignItems::Center,         // Center text vertically
@@ -186,6 +1091,7 @@ fn spawn_menu_button(
 // ===== UI INTERACTION SYSTEM =====
 
 /// Handle button interactions (hover, click effects)
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (&Interaction, &MenuButton, &mut BackgroundColor, &Children),
@@ -193,17 +1099,56 @@ fn button_system(
     >,
     mut text_query: Query<&mut TextColor>,
     mut exit: EventWriter<AppExit>,            // For quitting the application
+    mut settings: ResMut<BoidSettings>,
+    mut audio: ResMut<AudioSettings>,
+    mut menu_column: Query<&mut Node, (With<MainMenuColumn>, Without<SettingsPanel>)>,
+    mut settings_panel: Query<&mut Node, (With<SettingsPanel>, Without<MainMenuColumn>)>,
+    mut main_menu: Query<&mut Node, (With<MainMenu>, Without<MainMenuColumn>, Without<SettingsPanel>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
 ) {
     for (interaction, button_type, mut color, children) in &mut interaction_query {
         // Determine text color based on interaction state
         let text_color_value = match *interaction {
             Interaction::Pressed => {
                 // Handle button actions
-                match button_type {
+                // Other buttons don't have actions yet
+                match *button_type {
                     MenuButton::Quit => {
                         exit.write(AppExit::Success);  // Exit application
                     }
-                    _ => {}  // Other buttons don't have actions yet
+                    MenuButton::Settings | MenuButton::Back => {
+                        // Swap the button column and the settings panel
+                        let open = matches!(button_type, MenuButton::Settings);
+                        for mut node in &mut menu_column {
+                            node.display = if open { Display::None } else { Display::Flex };
+                        }
+                        for mut node in &mut settings_panel {
+                            node.display = if open { Display::Flex } else { Display::None };
+                        }
+                    }
+                    MenuButton::Adjust(field, step) => field.adjust(&mut settings, &mut audio, step),
+                    MenuButton::Resolution(index) => {
+                        let (width, height) = RESOLUTION_PRESETS[index];
+                        for mut window in &mut windows {
+                            window.resolution.set(width, height);
+                        }
+                        info!("Resolution: {width}x{height}");
+                    }
+                    MenuButton::SinglePlayer => {
+                        for mut node in &mut main_menu {
+                            node.display = Display::None;
+                        }
+                        next_state.set(GameState::Running);
+                    }
+                    MenuButton::Retry => next_state.set(GameState::Restarting),
+                    MenuButton::MainMenu => {
+                        for mut node in &mut main_menu {
+                            node.display = Display::Flex;
+                        }
+                        next_state.set(GameState::Menu);
+                    }
+                    _ => {}
                 }
                 Color::srgb(0.6, 0.6, 0.6)  // Dark gray when pressed
             }
@@ -223,526 +1168,6804 @@ fn button_system(
     }
 }
 
-// ===== BOID SETUP AND SIMULATION =====
+// ===== MENU AMBIENCE =====
 
-/// Initialize the boid population with different types
-fn setup_boids(
+/// Purely decorative boid drifting behind the main menu
+///
+/// Not a `Boid`, so turrets, waves and the flock systems never see it.
+#[derive(Component)]
+struct AmbientBoid {
+    velocity: Vec2,
+}
+
+fn spawn_ambient_boids(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    visual_assets: Res<VisualAssets>,
+    bounds: Res<WorldBounds>,
 ) {
-    let Ok(window) = window_query.single() else { return; };
+    let half = bounds.size / 2.0;
     let mut rng = rand::rng();
     
-    // Spawn main flock of 150 white boids with random positions and velocities
-    for _ in 0..150 {
-        // Random position within window bounds
-        let position = Vec2::new(
-            rng.random_range(-window.width() / 2.0..window.width() / 2.0),
-            rng.random_range(-window.height() / 2.0..window.height() / 2.0),
-        );
-        
-        // Start with varied but consistent velocities for natural movement
-        let angle = rng.random_range(0.0..std::f32::consts::TAU);  // TAU = 2π
-        let speed = rng.random_range(100.0..300.0);
-        let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
-        
+    let count = 60;
+    let speed = 60.0;  // A slow drift, well under gameplay speeds
+    for _ in 0..count {
+        let position = Vec2::new(rng.random_range(-half.x..half.x), rng.random_range(-half.y..half.y));
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
         commands.spawn((
-            Boid {
-                velocity,
-                acceleration: Vec2::ZERO,
-                health: 1.0,  // Full health
-                damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
-            },
-            Transform::from_translation(position.extend(0.0)),  // Convert Vec2 to Vec3
+            Mesh2d(visual_assets.boid_mesh.clone()),
+            MeshMaterial2d(visual_assets.ambient_boid_material.clone()),
+            Transform::from_translation(position.extend(-0.5)),  // Behind the menu, above the background
+            AmbientBoid { velocity: Vec2::from_angle(angle) * speed },
         ));
-    }    
-    
+    }
 }
 
-/// Update boid movement using flocking algorithm (separation, alignment, cohesion)
-fn update_boids(
-    mut boids: Query<(&mut Boid, &mut Transform, Entity)>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+fn despawn_ambient_boids(mut commands: Commands, ambient: Query<Entity, With<AmbientBoid>>) {
+    for entity in &ambient {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Gentle alignment and cohesion with wrap-around edges
+///
+/// The menu flock is small, so a plain all-pairs scan is fine here.
+fn update_ambient_boids(
+    mut ambient: Query<(&mut AmbientBoid, &mut Transform)>,
+    bounds: Res<WorldBounds>,
     time: Res<Time>,
 ) {
-    let Ok(window) = window_query.single() else { return; };
-    let half_width = window.width() / 2.0;
-    let half_height = window.height() / 2.0;
+    let half = bounds.size / 2.0;
     
-    // Collect all boid positions and velocities for flocking calculations
-    // This prevents borrowing issues when calculating neighbor interactions
-    let boids_data: Vec<(Vec2, Vec2, Entity)> = boids
+    let perception_radius = 120.0;
+    let max_speed = 80.0;
+    let min_speed = 40.0;
+    let steer = 0.5;  // Fraction of the velocity difference corrected per second
+    
+    let snapshot: Vec<(Vec2, Vec2)> = ambient
         .iter()
-        .map(|(boid, transform, entity)| {
-            (transform.translation.truncate(), boid.velocity, entity)
-        })
+        .map(|(boid, transform)| (transform.translation.truncate(), boid.velocity))
         .collect();
     
-    for (mut boid, mut transform, entity) in &mut boids {
+    for (mut boid, mut transform) in &mut ambient {
         let pos = transform.translation.truncate();
+        let neighbors: Vec<&(Vec2, Vec2)> = snapshot
+            .iter()
+            .filter(|(other, _)| *other != pos && other.distance(pos) < perception_radius)
+            .collect();
         
-        // Update damage flash timer
-        boid.damage_flash_timer.tick(time.delta());
-        
-        // ===== EDGE AVOIDANCE FORCE =====
-        // Apply forces to keep boids away from screen edges with smooth curves
-        let edge_margin = 150.0;     // Distance from edge where force starts
-        let edge_force = 900.0;      // Maximum force strength
-        
-        // Right edge avoidance
-        if pos.x > half_width - edge_margin {
-            let distance_to_edge = half_width - pos.x;
-            let force = (1.0 - distance_to_edge / edge_margin).powf(2.0) * edge_force;
-            boid.acceleration.x -= force;  // Push left
-        } 
-        // Left edge avoidance
-        else if pos.x < -half_width + edge_margin {
-            let distance_to_edge = pos.x + half_width;
-            let force = (1.0 - distance_to_edge / edge_margin).powf(2.0) * edge_force;
-            boid.acceleration.x += force;  // Push right
-        }
-        
-        // Top edge avoidance
-        if pos.y > half_height - edge_margin {
-            let distance_to_edge = half_height - pos.y;
-            let force = (1.0 - distance_to_edge / edge_margin).powf(2.0) * edge_force;
-            boid.acceleration.y -= force;  // Push down
-        } 
-        // Bottom edge avoidance
-        else if pos.y < -half_height + edge_margin {
-            let distance_to_edge = pos.y + half_height;
-            let force = (1.0 - distance_to_edge / edge_margin).powf(2.0) * edge_force;
-            boid.acceleration.y += force;  // Push up
-        }
-        
-        // ===== FLOCKING BEHAVIOR (Craig Reynolds' Boids Algorithm) =====
-        let mut separation = Vec2::ZERO;  // Avoid crowding neighbors
-        let mut alignment = Vec2::ZERO;   // Steer towards average heading of neighbors
-        let mut cohesion = Vec2::ZERO;    // Steer towards average position of neighbors
-        let mut neighbors = 0;
-        
-        let perception_radius = 100.0;  // How far boids can "see" each other
-        let max_speed = 300.0;          // Maximum movement speed
-        let max_force = 400.0;          // Maximum steering force
-        
-        // Check all other boids for flocking interactions
-        for &(other_pos, other_vel, other_entity) in &boids_data {
-            if entity == other_entity {
-                continue;  // Skip self
-            }
-            
-            let distance = pos.distance(other_pos);
-            
-            // Only consider boids within perception range
-            if distance < perception_radius && distance > 0.0 {
-                // SEPARATION: Avoid crowding (most important for natural movement)
-                if distance < 40.0 {  // Personal space radius
-                    let diff = (pos - other_pos).normalize_or_zero();
-                    let force_strength = (40.0 - distance) / 40.0;  // Stronger when closer
-                    separation += diff * force_strength;
-                }
-                
-                // ALIGNMENT: Match velocity of neighbors
-                alignment += other_vel;
-                
-                // COHESION: Move towards center of local group
-                cohesion += other_pos;
-                
-                neighbors += 1;
-            }
+        if !neighbors.is_empty() {
+            let count = neighbors.len() as f32;
+            let average_velocity = neighbors.iter().map(|(_, velocity)| *velocity).sum::<Vec2>() / count;
+            let center = neighbors.iter().map(|(position, _)| *position).sum::<Vec2>() / count;
+            let toward_center = (center - pos).normalize_or_zero() * max_speed;
+            let desired = (average_velocity + toward_center) / 2.0;
+            let steering = (desired - boid.velocity) * steer * time.delta_secs();
+            boid.velocity += steering;
         }
+        boid.velocity = boid.velocity.clamp_length(min_speed, max_speed);
         
-        // Apply flocking forces if neighbors were found
-        if neighbors > 0 {
-            // Calculate average values
-            alignment /= neighbors as f32;
-            cohesion /= neighbors as f32;
-            cohesion = cohesion - pos;  // Vector towards center
-            
-            // Convert to steering forces (desired velocity - current velocity)
-            if separation.length() > 0.0 {
-                separation = separation.normalize() * max_force;
-            }
-            if alignment.length() > 0.0 {
-                let desired = alignment.normalize() * max_speed;
-                alignment = (desired - boid.velocity) * 1.0;  // Gentle alignment
-            }
-            if cohesion.length() > 0.0 {
-                let desired = cohesion.normalize() * max_speed;
-                cohesion = (desired - boid.velocity) * 0.6;  // Gentle cohesion
-            }
-            
-            // Apply forces with different weights for natural behavior
-            boid.acceleration += separation * 1.0;  // Separation is most important
-            boid.acceleration += alignment;         // Medium importance
-            boid.acceleration += cohesion;          // Least important
+        // Wrap around instead of steering off the edges
+        let mut new_pos = pos + boid.velocity * time.delta_secs();
+        if new_pos.x.abs() > half.x {
+            new_pos.x = -new_pos.x.signum() * half.x;
         }
-    
-        
-        // ===== VELOCITY AND POSITION UPDATES =====
-        // Apply acceleration to velocity with damping for smoother movement
-        let acceleration_delta = boid.acceleration * time.delta_secs();
-        boid.velocity += acceleration_delta;
-        boid.velocity *= 0.99;  // Slight damping to prevent excessive speed buildup
-        boid.velocity = boid.velocity.clamp_length_max(max_speed);
-
-        // Ensure minimum speed to prevent boids from stopping completely
-        if boid.velocity.length() < 100.0 {
-            boid.velocity = boid.velocity.normalize_or_zero() * 100.0;
+        if new_pos.y.abs() > half.y {
+            new_pos.y = -new_pos.y.signum() * half.y;
         }
-        
-        // Update position based on velocity
-        transform.translation.x += boid.velocity.x * time.delta_secs();
-        transform.translation.y += boid.velocity.y * time.delta_secs();
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+        transform.rotation = Quat::from_rotation_z(boid.velocity.to_angle() - std::f32::consts::FRAC_PI_2);
     }
 }
 
-// / Create and update visual representations of boids (triangular meshes)
-fn draw_boids(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    boids: Query<(Entity, &Transform, &Boid), Without<BoidVisual>>,  // Boids without visuals
-    mut visuals: Query<(&mut Transform, &ChildOf, &mut MeshMaterial2d<ColorMaterial>), With<BoidVisual>>,
-) {
-    // Create triangle mesh pointing forward (used for all boids)
-    let triangle_mesh = meshes.add(Triangle2d::new(
-        Vec2::new(0.0, 5.0),    // Top point (forward)
-        Vec2::new(-3.0, -3.0),  // Bottom left
-        Vec2::new(3.0, -3.0),   // Bottom right
-    ));
-    
-    // Create visual representations for boids that don't have them yet
-    for (entity, transform, _boid) in &boids {
-        // Check if this boid already has a visual child
-        if visuals.iter().find(|(_, child_of, _)| child_of.parent() == entity).is_none() {
-            // Determine boid color based on position and Z-coordinate
-            let base_color = if transform.translation.z > 0.5 {  // Special boids
-                if transform.translation.x > 100.0 {
-                    Color::srgb(1.0, 0.0, 0.5)  // Pink boid
-                } else {
-                    Color::srgb(1.0, 0.2, 0.2)  // Red boids
-                }
-            } else {
-                Color::WHITE  // Normal flock members
-            };
-            
-            let material = materials.add(ColorMaterial::from(base_color));
-            
-            // Create the visual mesh entity
-            let visual = commands
-                .spawn((
-                    Mesh2d(triangle_mesh.clone()),
-                    MeshMaterial2d(material),
-                    Transform::from_scale(Vec3::splat(1.0)),
-                    BoidVisual,
-                ))
-                .id();
-            
-            // Make visual a child of the boid entity
-            commands.entity(entity).add_child(visual);
+// ===== BOID SETUP AND SIMULATION =====
+
+/// Population size and flocking tunables shared by the boid systems
+///
+/// Persisted to `SETTINGS_PATH` on exit and loaded back at startup.
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]  // Fields missing from an older file keep their defaults
+struct BoidSettings {
+    target_count: usize,       // Boids in the first wave, spawned at startup
+    perception_radius: f32,    // How far boids can "see" each other
+    max_speed: f32,            // Maximum movement speed
+    max_force: f32,            // Maximum steering force
+    separation_radius: f32,    // Personal space radius
+    separation_weight: f32,    // Separation is most important
+    alignment_weight: f32,     // Medium importance
+    cohesion_weight: f32,      // Least important
+    flock_by_kind: bool,       // Only align and cohere with the same kind; separation still applies to all
+    field_of_view: f32,        // Width of the vision cone around the heading in degrees (360 sees all around)
+    follow_leader_weight: f32, // Pull towards the `Leader` for boids within `leader_follow_radius`
+    leader_follow_radius: f32, // How close a boid must be to the leader to follow it
+    prevent_overlap: bool,     // Push overlapping boids apart after they move (an extra grid pass)
+    body_radius: f32,          // Half the closest two boids may get with `prevent_overlap` on
+    max_turn_rate: f32,        // Fastest a boid's heading can swing, in degrees per second
+    tutorial_done: bool,       // The first-run tutorial was finished or skipped, so it isn't shown again
+}
+
+impl Default for BoidSettings {
+    fn default() -> Self {
+        Self {
+            target_count: 150,
+            perception_radius: 100.0,
+            max_speed: 300.0,
+            max_force: 400.0,
+            separation_radius: 40.0,
+            separation_weight: 1.0,
+            alignment_weight: 1.0,
+            cohesion_weight: 0.6,
+            flock_by_kind: false,
+            field_of_view: 270.0,  // Blind spot straight behind
+            follow_leader_weight: 1.5,  // Stronger than cohesion, so followers stick to it
+            leader_follow_radius: 250.0,
+            prevent_overlap: false,
+            body_radius: 4.0,  // About half the boid triangle's size
+            max_turn_rate: 270.0,
+            tutorial_done: false,
         }
     }
-    
-    // Update existing visual representations
-    for (mut visual_transform, child_of, material_handle) in &mut visuals {
-        if let Ok((_, transform, boid)) = boids.get(child_of.parent()) {
-            // Update rotation to point in movement direction
-            let angle = boid.velocity.y.atan2(boid.velocity.x) - std::f32::consts::FRAC_PI_2;
-            visual_transform.rotation = Quat::from_rotation_z(angle);
-            
-            // Update color based on health and damage state
-            if let Some(material) = materials.get_mut(&material_handle.0) {
-                // Determine base color (same logic as creation)
-                let base_color = if transform.translation.z > 0.5 {
-                    if transform.translation.x > 100.0 {
-                        Color::srgb(1.0, 0.0, 0.5)  // Pink
-                    } else {
-                        Color::srgb(1.0, 0.2, 0.2)  // Red
-                    }
-                } else {
-                    Color::WHITE
-                };
-                
-                // Apply damage flash effect if timer is active
-                if !boid.damage_flash_timer.finished() {
-                    // Create flashing effect with sine wave
-                    let flash_progress = boid.damage_flash_timer.elapsed_secs() / boid.damage_flash_timer.duration().as_secs_f32();
-                    let flash_intensity = (flash_progress * 10.0 * std::f32::consts::PI).sin().abs();
-                    
-                    // Flash to bright red regardless of base color
-                    material.color = if flash_intensity > 0.5 {
-                        Color::srgb(1.0, 0.0, 0.0)  // Bright red flash
-                    } else {
-                        base_color
-                    };
-                } else if boid.health < 1.0 {
-                    // Show damage by darkening the color based on health
-                    let health_factor = boid.health;
-                    let srgba = base_color.to_srgba();
-                    material.color = Color::srgb(
-                        srgba.red * health_factor,
-                        srgba.green * health_factor,
-                        srgba.blue * health_factor,
-                    );
-                } else {
-                    // Full health - use normal base color
-                    material.color = base_color;
-                }
-            }
+}
+
+impl BoidSettings {
+    /// Pull panel-adjustable values from a hand-edited or corrupt file back into the panel's ranges
+    fn sanitize(&mut self) {
+        let defaults = Self::default();
+        let clamp = |field: SettingField, value: f32, default: f32| {
+            let (min, max) = field.range();
+            if value.is_finite() { value.clamp(min, max) } else { default }
+        };
+        self.perception_radius = clamp(SettingField::PerceptionRadius, self.perception_radius, defaults.perception_radius);
+        self.max_speed = clamp(SettingField::MaxSpeed, self.max_speed, defaults.max_speed);
+        self.target_count = clamp(SettingField::TargetCount, self.target_count as f32, defaults.target_count as f32) as usize;
+    }
+}
+
+/// Where `BoidSettings` are saved between runs
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// Load saved flock settings, keeping the defaults if the file is missing or unreadable
+fn load_settings(mut settings: ResMut<BoidSettings>) {
+    let text = match std::fs::read_to_string(SETTINGS_PATH) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+        Err(error) => {
+            warn!("Could not read {SETTINGS_PATH}: {error}");
+            return;
+        }
+    };
+    match ron::from_str::<BoidSettings>(&text) {
+        Ok(mut loaded) => {
+            loaded.sanitize();
+            *settings = loaded;
+            info!("Loaded flock settings from {SETTINGS_PATH}");
         }
+        Err(error) => warn!("Ignoring malformed {SETTINGS_PATH}: {error}"),
     }
 }
 
-// ===== TURRET SYSTEMS =====
+/// Save the flock settings when the app is closing
+fn save_settings(mut exit_events: EventReader<AppExit>, settings: Res<BoidSettings>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    
+    let result = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| std::fs::write(SETTINGS_PATH, text).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => info!("Saved flock settings to {SETTINGS_PATH}"),
+        Err(error) => warn!("Could not save {SETTINGS_PATH}: {error}"),
+    }
+}
 
-/// Create defensive turrets at strategic positions around the map
-fn setup_turrets(
+/// Named (separation, alignment, cohesion) weight presets, the defaults first
+const WEIGHT_PROFILES: [(&str, f32, f32, f32); 3] = [
+    ("Default", 1.0, 1.0, 0.6),
+    ("Tight school", 0.8, 1.5, 1.2),   // Strong alignment and cohesion: one dense stream
+    ("Loose swarm", 1.8, 0.4, 0.3),    // Mostly separation: boids spread out and jitter
+];
+
+/// Cycle the flocking weights through `WEIGHT_PROFILES` with F4, for comparing styles
+fn cycle_weight_profile(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<BoidSettings>) {
+    if !keyboard.just_pressed(KeyCode::F4) {
+        return;
+    }
+    
+    // Custom weights (e.g. loaded from disk) restart the cycle at the first preset
+    let current = WEIGHT_PROFILES.iter().position(|&(_, separation, alignment, cohesion)| {
+        (separation, alignment, cohesion)
+            == (settings.separation_weight, settings.alignment_weight, settings.cohesion_weight)
+    });
+    let next = current.map_or(0, |index| (index + 1) % WEIGHT_PROFILES.len());
+    let (name, separation, alignment, cohesion) = WEIGHT_PROFILES[next];
+    settings.separation_weight = separation;
+    settings.alignment_weight = alignment;
+    settings.cohesion_weight = cohesion;
+    info!(
+        "Flock weights: {} (separation {}, alignment {}, cohesion {})",
+        name, separation, alignment, cohesion
+    );
+}
+
+/// Initialize the boid population with different types
+fn setup_boids(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut next_id: ResMut<NextBoidId>,
+    zone: Res<ProtectZone>,
+    goal: Res<GoalZone>,
+    settings: Res<BoidSettings>,
+    bounds: Res<WorldBounds>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    let Ok(window) = window_query.single() else { return; };
+    let playfield = bounds.size;
+    let rng = &mut game_rng.rng;
+    let spawn_clearance = 300.0;  // Keep the opening flock this far back from the goal zone
+    let mut spawned_positions: Vec<Vec2> = Vec::new();
     
-    // Create meshes for turret components
-    let turret_base = meshes.add(Rectangle::new(20.0, 20.0));      // Square base
-    let turret_material = materials.add(ColorMaterial::from(Color::srgb(0.3, 0.3, 0.3)));  // Dark gray
+    // Spawn the flock split evenly across two teams with random positions and velocities
+    for i in 0..settings.target_count {
+        // One pink boid and a few red ones stand out from the flock
+        let kind = match i {
+            0 => BoidKind::Pink,
+            1..=4 => BoidKind::Red,
+            _ => BoidKind::random(rng),
+        };
+        
+        // Random position within the playfield, outside the protected and goal zones and
+        // apart from the boids already placed. A playfield too crowded (or too small) for
+        // that settles for the last candidate rather than searching forever.
+        let mut position = Vec2::ZERO;
+        for _ in 0..MAX_SPAWN_ATTEMPTS {
+            position = Vec2::new(
+                rng.random_range(-playfield.x / 2.0..playfield.x / 2.0),
+                rng.random_range(-playfield.y / 2.0..playfield.y / 2.0),
+            );
+            let clear_of_goal = !goal.enabled || goal.rect(playfield).min.x - position.x > spawn_clearance;
+            let spaced = spawned_positions.iter().all(|other| other.distance(position) >= MIN_SPAWN_SPACING);
+            if !zone.contains(position) && clear_of_goal && spaced {
+                break;
+            }
+        }
+        spawned_positions.push(position);
+        
+        // Start with varied but consistent velocities for natural movement
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);  // TAU = 2π
+        let speed = rng.random_range(100.0..300.0);
+        let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
+        
+        let id = next_id.next();
+        let entity = commands.spawn((
+            Boid {
+                velocity,
+                acceleration: Vec2::ZERO,
+                health: kind.max_health(),  // Full health
+                max_health: kind.max_health(),
+                max_speed: settings.max_speed * kind.speed_scale(),
+                energy: 1.0,  // Fully rested
+                damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
+                team: (i % 2) as u8,  // Alternate teams so both swarms start mixed
+                generation: 0,
+            },
+            kind,
+            id,
+            Transform::from_translation(position.extend(0.0)),  // Convert Vec2 to Vec3
+        )).id();
+        commands.trigger(BoidSpawned { entity, id });
+    }    
     
-    // Strategic turret positions for good map coverage
-    let positions = vec![
-        Vec2::new(-window.width() / 3.0, -window.height() / 3.0),  // Bottom left
-        Vec2::new(window.width() / 3.0, -window.height() / 3.0),   // Bottom right
-        Vec2::new(0.0, window.height() / 3.0),                     // Top center
-        Vec2::new(-window.width() / 4.0, window.height() / 4.0),   // Top left
-        Vec2::new(window.width() / 4.0, window.height() / 4.0),    // Top right
-    ];
+    // A single predator hunts the flock from a random spot on the playfield edge
+    commands.spawn((
+        Predator {
+            velocity: Vec2::ZERO,
+            speed: 220.0,         // Slower than a rested boid, faster than an exhausted one
+            catch_radius: 15.0,
+        },
+        Transform::from_translation(random_edge_position(rng, playfield).extend(0.2)),
+        Visibility::default(),  // Lets the child triangle inherit visibility
+    ));
     
-    for pos in positions {
-        // Spawn turret base with targeting logic
-        commands
-            .spawn((
-                Mesh2d(turret_base.clone()),
-                MeshMaterial2d(turret_material.clone()),
-                Transform::from_translation(pos.extend(-1.0)),  // Behind boids in Z-order
-                Turret {
-                    target: None,                                    // No initial target
-                    range: 250.0,                                   // Targeting range
-                    cooldown_timer: Timer::from_seconds(0.5, TimerMode::Once),  // Target acquisition delay
-                },
-            ))
-            .with_children(|parent| {
-                // Spawn turret barrel as child (rotates with targeting)
-                parent.spawn((
-                    MeshMaterial2d(turret_material.clone()),
-                    Transform::from_xyz(0.0, 10.0, 0.1),  // Offset forward from base
-                ));
-            });
-    }
+    // The leader circles the middle of the playfield, collecting a following as it goes
+    let leader = Leader::default();
+    commands.spawn((
+        Transform::from_translation(leader.position().extend(0.2)),
+        leader,
+        Visibility::default(),
+    ));
 }
 
-/// Update turret targeting logic and create laser beams
-fn update_turrets(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut turrets: Query<(Entity, &mut Turret, &Transform, &Children)>,
-    boids: Query<(&Transform, Entity), (With<Boid>, Without<Turret>)>,
-    existing_beams: Query<&LaserBeam>,
+/// Update boid movement using flocking algorithm (separation, alignment, cohesion)
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_boids(
+    mut boids: Query<(&mut Boid, &mut Transform, Entity, &BoidId, &BoidKind)>,
+    bounds: Res<WorldBounds>,
+    curiosity: Res<Curiosity>,
+    formation: Res<Formation>,
+    integrator: Res<Integrator>,
+    boundary: Res<BoundaryMode>,
+    leader_path: Res<LeaderPath>,
+    stamina: Res<Stamina>,
+    wells: Query<(&GravityWell, &Transform), Without<Boid>>,
+    cursor_force: Res<CursorForce>,
+    panic: Res<PanicTimer>,
+    mut grid: ResMut<SpatialGrid>,
+    // The next two are paired to stay within Bevy's 16 system parameters
+    (settings, difficulty): (Res<BoidSettings>, Res<Difficulty>),
+    (predators, leaders): (
+        Query<&Transform, (With<Predator>, Without<Boid>)>,
+        Query<&Transform, (With<Leader>, Without<Boid>)>,
+    ),
+    obstacles: Query<(&Obstacle, &Transform), Without<Boid>>,
     time: Res<Time>,
 ) {
-    for (turret_entity, mut turret, turret_transform, _children) in &mut turrets {
-        // Update targeting cooldown timer
-        turret.cooldown_timer.tick(time.delta());
+    let playfield = bounds.size;
+    let half_width = playfield.x / 2.0;
+    let half_height = playfield.y / 2.0;
+    
+    // Bucket all boid positions and velocities for flocking calculations
+    // This prevents borrowing issues when calculating neighbor interactions
+    grid.rebuild(boids.iter().map(|(boid, transform, entity, _, kind)| {
+        (transform.translation.truncate(), boid.velocity, entity, boid.team, *kind)
+    }));
+    let grid = &*grid;
+    let mut nearby = Vec::new();
+    let mut neighbors = Vec::new();
+    
+    // Panicking boids flee the flock's center of mass
+    let panic_strength = panic.current_strength();
+    let flock_center = (panic_strength > 0.0 && !grid.boids.is_empty())
+        .then(|| grid.boids.iter().map(|&(pos, ..)| pos).sum::<Vec2>() / grid.boids.len() as f32);
+    
+    for (mut boid, mut transform, entity, id, kind) in &mut boids {
+        let pos = transform.translation.truncate();
         
-        // ===== TARGET VALIDATION =====
-        // Check if current target is still valid and within range
-        let mut target_valid = false;
-        if let Some(target_entity) = turret.target {
-            if let Ok((boid_transform, _)) = boids.get(target_entity) {
-                let distance = turret_transform
-                    .translation
-                    .truncate()
-                    .distance(boid_transform.translation.truncate());
-                target_valid = distance < turret.range;
+        // Update damage flash timer
+        boid.damage_flash_timer.tick(time.delta());
+        
+        // Boids assigned to a formation point steer there instead of flocking
+        let formation_target = formation.assignments.get(&entity).copied();
+        
+        // ===== OBSTACLE AVOIDANCE =====
+        // Push out from obstacles with the same curve as the edges, plus a sideways
+        // push along the boid's heading so it slides around instead of bouncing off
+        let obstacle_margin = 60.0;  // Distance from the obstacle surface where force starts
+        for (obstacle, obstacle_transform) in &obstacles {
+            let away = pos - obstacle_transform.translation.truncate();
+            let gap = away.length() - obstacle.radius;
+            if gap < obstacle_margin {
+                let normal = away.normalize_or_zero();
+                let force = (1.0 - gap.max(0.0) / obstacle_margin).powf(2.0) * EDGE_FORCE;
+                let mut tangent = normal.perp();
+                if tangent.dot(boid.velocity) < 0.0 {
+                    tangent = -tangent;  // Keep going the way it was already turning
+                }
+                boid.acceleration += normal * force + tangent * force * 0.5;
             }
         }
         
-        // If target is lost, clear it and start cooldown before finding new target
-        if !target_valid && turret.target.is_some() {
-            turret.target = None;
-            turret.cooldown_timer.reset();
+        // ===== EDGES AND FLOCKING =====
+        // Sort out who's nearby and how this boid relates to each of them; the
+        // steering itself is worked out by `compute_boid_acceleration`
+        let max_speed = boid.max_speed * difficulty.speed_multiplier;
+        let max_force = settings.max_force;
+        let max_turn_rate = settings.max_turn_rate.to_radians();
+        let team = boid.team;
+        
+        grid.query(pos, settings.perception_radius.max(TEAM_REPULSION_RADIUS), &mut nearby);
+        neighbors.clear();
+        neighbors.extend(nearby.iter().filter_map(|&index| {
+            let (other_pos, other_vel, other_entity, other_team, other_kind) = grid.boids[index];
+            (other_entity != entity).then_some(Neighbor {
+                position: other_pos,
+                velocity: other_vel,
+                rival: other_team != team,
+                // Formation boids only keep their distance, as do other kinds when flocking by kind
+                cohere: formation_target.is_none() && (!settings.flock_by_kind || other_kind == *kind),
+            })
+        }));
+        let edges = (*boundary == BoundaryMode::Steer).then_some(&*bounds);  // Wrap and Bounce resolve edges after moving
+        let steering = compute_boid_acceleration(pos, boid.velocity, max_speed, &neighbors, edges, &settings);
+        boid.acceleration += steering;
+        
+        // ===== FORMATION SEEKING =====
+        // Arrive at the assigned formation point, slowing down on approach
+        if let Some(target) = formation_target {
+            let offset = target - pos;
+            let distance = offset.length();
+            let arrive_radius = 100.0;  // Distance at which boids start braking
+            let speed = max_speed * (distance / arrive_radius).min(1.0);
+            let desired = offset.normalize_or_zero() * speed;
+            let steering = (desired - boid.velocity) * 3.0;  // Overrides flocking
+            boid.acceleration += steering;
         }
         
-        // ===== TARGET ACQUISITION =====
-        // Find new target only after cooldown expires
-        if turret.target.is_none() && turret.cooldown_timer.finished() {
-            let mut closest_distance = f32::MAX;
-            
-            // Search for closest boid within range
-            for (boid_transform, boid_entity) in &boids {
-                let distance = turret_transform
-                    .translation
-                    .truncate()
-                    .distance(boid_transform.translation.truncate());
-                
-                if distance < turret.range && distance < closest_distance {
-                    closest_distance = distance;
-                    turret.target = Some(boid_entity);
-                }
+        // ===== CURIOSITY TOWARDS NEW TURRETS =====
+        // Briefly steer towards freshly placed turrets, fading as the novelty wears off
+        for source in &curiosity.sources {
+            let to_turret = source.position - pos;
+            let distance = to_turret.length();
+            if distance < curiosity.radius && distance > 0.0 {
+                let novelty = source.timer.remaining_secs() / source.timer.duration().as_secs_f32();
+                boid.acceleration += to_turret / distance * curiosity.strength * novelty;
             }
         }
         
-        // ===== BARREL ROTATION AND LASER CREATION =====
-        if let Some(target_entity) = turret.target {
-            if let Ok((boid_transform, _)) = boids.get(target_entity) {
-                // Calculate direction to target
-                let direction = (boid_transform.translation.truncate() - turret_transform.translation.truncate()).normalize();
-                let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
-                
-                // Create laser beam if one doesn't exist for this turret
-                let has_beam = existing_beams.iter().any(|beam| beam.turret == turret_entity);
-                if !has_beam {
-                    let distance = turret_transform
-                        .translation
-                        .truncate()
-                        .distance(boid_transform.translation.truncate());
-                    
-                    // Create laser mesh spanning the distance to target
-                    let laser_mesh = meshes.add(Rectangle::new(2.0, distance));
-                    let laser_material = materials.add(ColorMaterial::from(Color::srgba(1.0, 0.0, 0.0, 0.7)));  // Semi-transparent red
-                    
-                    // Spawn laser beam positioned between turret and target
-                    commands.spawn((
-                        Mesh2d(laser_mesh),
-                        MeshMaterial2d(laser_material),
-                        Transform::from_translation(turret_transform.translation + (direction * distance / 2.0).extend(0.0))
-                            .with_rotation(Quat::from_rotation_z(angle)),
-                        LaserBeam { turret: turret_entity },
-                    ));
+        // ===== PREDATOR AVOIDANCE =====
+        // Flee nearby predators harder than any flocking urge; the pull fades smoothly
+        // to zero at the edge of the flee radius so distant predators cause no jitter
+        let flee_radius = 200.0;
+        let flee_weight = 2.0;
+        for predator_transform in &predators {
+            let away = pos - predator_transform.translation.truncate();
+            let distance = away.length();
+            if distance < flee_radius && distance > 0.0 {
+                let urgency = (1.0 - distance / flee_radius).powi(2);
+                let desired = away / distance * max_speed;
+                let steering = (desired - boid.velocity) * flee_weight * urgency;
+                boid.acceleration += steering;
+            }
+        }
+        
+        // ===== GRAVITY WELLS =====
+        // Inverse-square pull, softened near the center so boids slingshot instead of sticking
+        for (well, well_transform) in &wells {
+            let to_well = well_transform.translation.truncate() - pos;
+            let distance_sq = to_well.length_squared().max(well.core_radius * well.core_radius);
+            let pull = (well.strength / distance_sq).min(max_force);
+            boid.acceleration += to_well.normalize_or_zero() * pull;
+        }
+        
+        // ===== CURSOR FORCE =====
+        // Held Ctrl-clicks pull boids toward (or push them away from) the cursor, fading out at the radius
+        if let Some(cursor) = cursor_force.point {
+            let to_cursor = cursor - pos;
+            let distance = to_cursor.length();
+            if distance < cursor_force.radius {
+                let direction = if cursor_force.repel { -to_cursor } else { to_cursor };
+                let falloff = 1.0 - distance / cursor_force.radius;
+                boid.acceleration += direction.normalize_or_zero() * cursor_force.strength * falloff;
+            }
+        }
+        
+        // ===== PANIC DISPERSAL =====
+        // Scatter outward from the flock's center. The wanted outward speed drops to zero
+        // part of the way to the edges, so boids spread out without being thrown into
+        // the walls or the goal zone; the push itself fades as the panic runs out.
+        if let Some(center) = flock_center {
+            // A boid right at the center bolts the way it's already heading
+            let away = (pos - center).try_normalize().unwrap_or(boid.velocity.normalize_or_zero());
+            let reach = 0.6;  // Fraction of the way to the edge the scatter carries boids
+            let outward = (pos.abs() / bounds.half_extents()).max_element();
+            let target_speed = max_speed * (1.0 - outward / reach).max(0.0);
+            let outward_speed = boid.velocity.dot(away);
+            let push = ((target_speed - outward_speed) * 4.0).clamp(-panic_strength, panic_strength);
+            boid.acceleration += away * push;
+        }
+        
+        // ===== LEADER PATH =====
+        // Leaders chase the scripted anchor; everyone else follows them through flocking
+        if leader_path.enabled && formation_target.is_none() && leader_path.is_leader(*id) {
+            let desired = (leader_path.anchor - pos).normalize_or_zero() * max_speed;
+            let steering = (desired - boid.velocity) * leader_path.pull;
+            boid.acceleration += steering;
+        }
+        
+        // ===== FOLLOW THE LEADER =====
+        // Boids near the scripted leader cohere on it rather than just their neighbors,
+        // braking as they arrive so they trail it instead of piling onto it
+        if formation_target.is_none() {
+            for leader_transform in &leaders {
+                let to_leader = leader_transform.translation.truncate() - pos;
+                let distance = to_leader.length();
+                if distance < settings.leader_follow_radius && distance > 0.0 {
+                    let arrive_radius = 80.0;  // Distance at which followers start braking
+                    let speed = max_speed * (distance / arrive_radius).min(1.0);
+                    let desired = to_leader / distance * speed;
+                    let steering = (desired - boid.velocity) * settings.follow_leader_weight;
+                    boid.acceleration += steering;
                 }
             }
         }
+    
+        
+        // ===== VELOCITY AND POSITION UPDATES =====
+        // Apply acceleration to velocity with damping for smoother movement
+        let previous_velocity = boid.velocity;
+        let acceleration_delta = boid.acceleration * time.delta_secs();
+        boid.velocity += acceleration_delta;
+        boid.velocity *= 0.99;  // Slight damping to prevent excessive speed buildup
+        boid.velocity = boid.velocity.clamp_length_max(stamina.max_speed(max_speed, boid.energy));
+        
+        // Limit how sharply the heading can swing this frame, however strong the force
+        boid.velocity = clamp_turn(previous_velocity, boid.velocity, max_turn_rate * time.delta_secs());
+
+        // Ensure minimum speed to prevent boids from stopping completely
+        // (formation members are allowed to settle on their point)
+        if formation_target.is_none() && boid.velocity.length() < 100.0 {
+            boid.velocity = boid.velocity.normalize_or_zero() * 100.0;
+        }
+        
+        // Sprinting drains energy, cruising restores it
+        let energy_rate = if boid.velocity.length() > stamina.sprint_speed {
+            -stamina.drain_rate
+        } else {
+            stamina.regen_rate
+        };
+        boid.energy = (boid.energy + energy_rate * time.delta_secs()).clamp(0.0, 1.0);
+        
+        // Update position based on velocity using the configured scheme
+        let new_pos = integrator.integrate_position(
+            pos,
+            previous_velocity,
+            boid.velocity,
+            boid.acceleration,
+            time.delta_secs(),
+        );
+        let (new_pos, velocity) = boundary.resolve(new_pos, boid.velocity, Vec2::new(half_width, half_height));
+        boid.velocity = velocity;
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+        
+        // Forces are re-accumulated from scratch every frame
+        boid.acceleration = Vec2::ZERO;
     }
 }
 
-/// Update laser beam positions and lengths to track moving targets
-fn update_lasers(
-    mut commands: Commands,
-    mut lasers: Query<(Entity, &LaserBeam, &mut Transform, &MeshMaterial2d<ColorMaterial>, &Mesh2d)>,
-    turrets: Query<(&Turret, &Transform), Without<LaserBeam>>,
-    boids: Query<&Transform, (With<Boid>, Without<LaserBeam>)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-) {
-    for (laser_entity, laser_beam, mut laser_transform, _, mesh_handle) in &mut lasers {
-        // Get the turret that owns this laser
-        if let Ok((turret, turret_transform)) = turrets.get(laser_beam.turret) {
-            // Check if turret still has a target
-            if let Some(target_entity) = turret.target {
-                if let Ok(boid_transform) = boids.get(target_entity) {
-                    // Update laser to connect turret and target
-                    let direction = boid_transform.translation.truncate() - turret_transform.translation.truncate();
-                    let distance = direction.length();
-                    let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
-                    
-                    // Position laser at midpoint between turret and target
-                    laser_transform.translation = turret_transform.translation + (direction.normalize() * distance / 2.0).extend(0.0);
-                    laser_transform.rotation = Quat::from_rotation_z(angle);
-                    
-                    // Update laser mesh length to match current distance
-                    if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
-                        *mesh = Rectangle::new(2.0, distance).into();
-                    }
-                } else {
-                    // Target entity no longer exists, remove laser
-                    commands.entity(laser_entity).despawn();
-                }
-            } else {
-                // Turret has no target, remove laser
-                commands.entity(laser_entity).despawn();
+// ===== FLOCKING MATH =====
+
+/// Distance from an edge where `BoundaryMode::Steer` starts pushing boids back
+const EDGE_MARGIN: f32 = 150.0;
+
+/// Push at the very edge; obstacle avoidance uses the same strength
+const EDGE_FORCE: f32 = 900.0;
+
+/// Distance at which rival teams start pushing apart
+const TEAM_REPULSION_RADIUS: f32 = 60.0;
+
+/// Another boid within sensing range, as seen by `compute_boid_acceleration`
+#[derive(Clone, Copy, Debug)]
+struct Neighbor {
+    position: Vec2,
+    velocity: Vec2,
+    rival: bool,    // On another team: only shoved away, never flocked with
+    cohere: bool,   // Counts towards alignment and cohesion; separation applies regardless
+}
+
+/// Edge avoidance plus Reynolds flocking for a single boid
+///
+/// Plain math with no ECS access, so it can be benchmarked or tested with synthetic
+/// neighbors. `update_boids` gathers the neighbors from the spatial grid and adds the
+/// situational forces (obstacles, predators, formations, ...) on top.
+///
+/// * `max_speed` - this boid's current top speed
+/// * `neighbors` - other boids within `settings.perception_radius` or `TEAM_REPULSION_RADIUS`
+/// * `bounds` - playfield whose edges to steer away from, or `None` to ignore the edges
+fn compute_boid_acceleration(
+    position: Vec2,
+    velocity: Vec2,
+    max_speed: f32,
+    neighbors: &[Neighbor],
+    bounds: Option<&WorldBounds>,
+    settings: &BoidSettings,
+) -> Vec2 {
+    let mut acceleration = Vec2::ZERO;
+    
+    // ===== EDGE AVOIDANCE FORCE =====
+    if let Some(bounds) = bounds {
+        acceleration += edge_avoidance(position, velocity, bounds.half_extents(), EDGE_MARGIN, EDGE_FORCE);
+    }
+    
+    // ===== FLOCKING BEHAVIOR (Craig Reynolds' Boids Algorithm) =====
+    let mut separation = Vec2::ZERO;  // Avoid crowding neighbors
+    let mut alignment = Vec2::ZERO;   // Steer towards average heading of neighbors
+    let mut cohesion = Vec2::ZERO;    // Steer towards average position of neighbors
+    let mut team_repulsion = Vec2::ZERO;  // Push away from boids of other teams
+    let mut flockmates = 0;
+    
+    let perception_radius = settings.perception_radius;
+    let max_force = settings.max_force;
+    let separation_radius = settings.separation_radius;
+    let min_cos_to_see = (settings.field_of_view.to_radians() / 2.0).cos();
+    
+    // A nearly stopped boid has no reliable heading, so it sees all around
+    let heading = (velocity.length() > 1.0).then(|| velocity.normalize());
+    
+    for neighbor in neighbors {
+        let distance = position.distance(neighbor.position);
+        
+        // Rival teams never flock together, they only shove each other away
+        if neighbor.rival {
+            if distance < TEAM_REPULSION_RADIUS && distance > 0.0 {
+                let diff = (position - neighbor.position).normalize_or_zero();
+                let force_strength = (TEAM_REPULSION_RADIUS - distance) / TEAM_REPULSION_RADIUS;
+                team_repulsion += diff * force_strength;
+            }
+            continue;
+        }
+        
+        // Flockmates in the blind spot behind are ignored (rivals above are felt regardless)
+        if let Some(heading) = heading
+            && heading.dot((neighbor.position - position).normalize_or_zero()) < min_cos_to_see
+        {
+            continue;
+        }
+        
+        // Only consider boids within perception range
+        if distance < perception_radius && distance > 0.0 {
+            // SEPARATION: Avoid crowding (most important for natural movement)
+            if distance < separation_radius {
+                let diff = (position - neighbor.position).normalize_or_zero();
+                let force_strength = (separation_radius - distance) / separation_radius;  // Stronger when closer
+                separation += diff * force_strength;
             }
+            
+            if !neighbor.cohere {
+                continue;
+            }
+            
+            // ALIGNMENT: Match velocity of neighbors
+            alignment += neighbor.velocity;
+            
+            // COHESION: Move towards center of local group
+            cohesion += neighbor.position;
+            
+            flockmates += 1;
+        }
+    }
+    
+    // Separation counts every nearby flockmate, whatever its kind
+    if separation.length() > 0.0 {
+        separation = separation.normalize() * max_force;
+        acceleration += separation * settings.separation_weight;
+    }
+    
+    // Apply flocking forces if flockmates were found
+    if flockmates > 0 {
+        // Calculate average values
+        alignment /= flockmates as f32;
+        cohesion /= flockmates as f32;
+        cohesion -= position;  // Vector towards center
+        
+        // Convert to steering forces (desired velocity - current velocity)
+        if alignment.length() > 0.0 {
+            let desired = alignment.normalize() * max_speed;
+            alignment = desired - velocity;
+        }
+        if cohesion.length() > 0.0 {
+            let desired = cohesion.normalize() * max_speed;
+            cohesion = desired - velocity;
         }
+        
+        // Apply forces with different weights for natural behavior
+        acceleration += alignment * settings.alignment_weight;
+        acceleration += cohesion * settings.cohesion_weight;
+    }
+    
+    // Inter-team repulsion forms a moving front where two swarms meet
+    if team_repulsion.length() > 0.0 {
+        acceleration += team_repulsion.normalize() * max_force * 1.2;
     }
+    
+    acceleration
 }
 
-/// Apply damage to boids being targeted by turrets
-fn apply_laser_damage(
-    mut commands: Commands,
-    turrets: Query<(&Turret, &Transform)>,
-    mut boids: Query<(Entity, &mut Boid, &Transform)>,
-    time: Res<Time>,
-) {
-    let damage_per_second = 0.5;  // Takes 2 seconds to kill a boid (1.0 health / 0.5 damage)
+/// Numerical scheme used to advance boid positions each frame
+///
+/// Velocity is always advanced as `v + a * dt` (then damped and clamped); the
+/// schemes differ in which velocity moves the boid. Press I to cycle.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+enum Integrator {
+    ExplicitEuler,       // Move with the velocity from the start of the step
+    #[default]
+    SemiImplicitEuler,   // Move with the updated velocity (most stable)
+    Verlet,              // Velocity Verlet: start velocity plus half the acceleration term
+}
+
+impl Integrator {
+    /// Position after one step of `dt` seconds
+    ///
+    /// * `previous_velocity` - velocity at the start of the step
+    /// * `velocity` - velocity at the end of the step (after damping and clamps)
+    /// * `acceleration` - acceleration applied during the step
+    fn integrate_position(
+        self,
+        position: Vec2,
+        previous_velocity: Vec2,
+        velocity: Vec2,
+        acceleration: Vec2,
+        dt: f32,
+    ) -> Vec2 {
+        match self {
+            Integrator::ExplicitEuler => position + previous_velocity * dt,
+            Integrator::SemiImplicitEuler => position + velocity * dt,
+            Integrator::Verlet => position + previous_velocity * dt + 0.5 * acceleration * dt * dt,
+        }
+    }
     
-    for (turret, turret_transform) in &turrets {
-        if let Some(target_entity) = turret.target {
-            if let Ok((boid_entity, mut boid, boid_transform)) = boids.get_mut(target_entity) {
-                // Verify target is still in range
-                let distance = turret_transform
-                    .translation
-                    .truncate()
-                    .distance(boid_transform.translation.truncate());
-                
-                if distance <= turret.range {
-                    // Apply damage over time
-                    boid.health -= damage_per_second * time.delta_secs();
-                    
-                    // Trigger damage flash effect
-                    if boid.damage_flash_timer.finished() {
-                        boid.damage_flash_timer = Timer::from_seconds(0.5, TimerMode::Once);
+    /// The next scheme in the cycle order
+    fn next(self) -> Self {
+        match self {
+            Integrator::ExplicitEuler => Integrator::SemiImplicitEuler,
+            Integrator::SemiImplicitEuler => Integrator::Verlet,
+            Integrator::Verlet => Integrator::ExplicitEuler,
+        }
+    }
+}
+
+/// Cycle the boid integration scheme with the I key
+fn cycle_integrator(keyboard: Res<ButtonInput<KeyCode>>, mut integrator: ResMut<Integrator>) {
+    if keyboard.just_pressed(KeyCode::KeyI) {
+        *integrator = integrator.next();
+        info!("Boid integrator: {:?}", *integrator);
+    }
+}
+
+/// Steering that keeps a boid off the playfield edges in `BoundaryMode::Steer`
+///
+/// Each nearby edge pushes inward on a smooth curve, plus a sideways push along the
+/// boid's heading so it runs along the boundary. In a corner the inward pushes add
+/// up diagonally instead of each cancelling the boid's motion, so it always has a
+/// way out rather than stalling and jittering.
+///
+/// * `half_extents` - half the playfield size, centered on the origin
+/// * `margin` - distance from an edge where the push starts
+/// * `strength` - push at the edge itself
+fn edge_avoidance(position: Vec2, velocity: Vec2, half_extents: Vec2, margin: f32, strength: f32) -> Vec2 {
+    let mut inward = Vec2::ZERO;
+    for axis in 0..2 {
+        let distance_to_edge = half_extents[axis] - position[axis].abs();
+        if distance_to_edge < margin {
+            let force = (1.0 - distance_to_edge / margin).powf(2.0) * strength;
+            inward[axis] = -position[axis].signum() * force;  // Back towards the middle
+        }
+    }
+    if inward == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    
+    // Slide the way the boid is already going; one heading straight in (or
+    // stopped) is turned anticlockwise, so it never dead-stops against the wall
+    let mut tangent = inward.normalize().perp();
+    if tangent.dot(velocity) < 0.0 {
+        tangent = -tangent;
+    }
+    inward + tangent * inward.length() * 0.5
+}
+
+/// What happens when a boid reaches the edge of the playfield
+///
+/// Press B to cycle.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+enum BoundaryMode {
+    #[default]
+    Steer,    // Soft avoidance force near the edges (edges are never reached)
+    Wrap,     // Teleport to the opposite edge
+    Bounce,   // Reflect off the edge and stay inside
+}
+
+impl BoundaryMode {
+    /// Position and velocity after resolving any edge crossing
+    ///
+    /// * `half_extents` - half the playfield size, centered on the origin
+    fn resolve(self, position: Vec2, velocity: Vec2, half_extents: Vec2) -> (Vec2, Vec2) {
+        let mut position = position;
+        let mut velocity = velocity;
+        match self {
+            BoundaryMode::Steer => {}
+            BoundaryMode::Wrap => {
+                for axis in 0..2 {
+                    if position[axis] > half_extents[axis] {
+                        position[axis] -= 2.0 * half_extents[axis];
+                    } else if position[axis] < -half_extents[axis] {
+                        position[axis] += 2.0 * half_extents[axis];
                     }
-                    
-                    // Destroy boid when health is depleted
-                    if boid.health <= 0.0 {
-                        commands.entity(boid_entity).despawn();
+                }
+            }
+            BoundaryMode::Bounce => {
+                for axis in 0..2 {
+                    // Only flip a component still heading out, so a boid can't stick to the wall
+                    if position[axis] > half_extents[axis] && velocity[axis] > 0.0
+                        || position[axis] < -half_extents[axis] && velocity[axis] < 0.0
+                    {
+                        velocity[axis] = -velocity[axis];
                     }
+                    position[axis] = position[axis].clamp(-half_extents[axis], half_extents[axis]);
                 }
             }
         }
+        (position, velocity)
+    }
+    
+    /// The next mode in the cycle order
+    fn next(self) -> Self {
+        match self {
+            BoundaryMode::Steer => BoundaryMode::Wrap,
+            BoundaryMode::Wrap => BoundaryMode::Bounce,
+            BoundaryMode::Bounce => BoundaryMode::Steer,
+        }
     }
 }
 
-/// Maintain boid population by spawning new boids when others are destroyed
-fn respawn_boids(
-    mut commands: Commands,
-    boids: Query<&Boid>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-) {
-    let Ok(window) = window_query.single() else { return; };
-    let boid_count = boids.iter().count();
-    let target_count = 150;  // Maintain population of 150 boids
-    
-    // Only respawn if population has dropped
-    if boid_count < target_count {
-        let mut rng = rand::rng();
-        
-        // Spawn up to 5 new boids per frame (gradual replenishment)
-        for _ in 0..(target_count - boid_count).min(5) {
-            // Choose random edge to spawn from (0=left, 1=right, 2=bottom, 3=top)
-            let edge = rng.random_range(0..4);
-            let position = match edge {
-                0 => Vec2::new(-window.width() / 2.0, rng.random_range(-window.height() / 2.0..window.height() / 2.0)),  // Left edge
-                1 => Vec2::new(window.width() / 2.0, rng.random_range(-window.height() / 2.0..window.height() / 2.0)),   // Right edge
-                2 => Vec2::new(rng.random_range(-window.width() / 2.0..window.width() / 2.0), -window.height() / 2.0),   // Bottom edge
-                _ => Vec2::new(rng.random_range(-window.width() / 2.0..window.width() / 2.0), window.height() / 2.0),    // Top edge
-            };
-            
-            // Random initial velocity
-            let velocity = Vec2::new(
-                rng.random_range(-150.0..150.0),
-                rng.random_range(-150.0..150.0),
-            );
-            
-            // Spawn new boid at edge
-            commands.spawn((
-                Boid {
-                    velocity,
-                    acceleration: Vec2::ZERO,
-                    health: 1.0,  // Full health
-                    damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
-                },
-                Transform::from_translation(position.extend(0.0)),  // Z=0 for normal boids
-            ));
-        }
+/// Cycle the playfield boundary mode with the B key
+fn cycle_boundary_mode(keyboard: Res<ButtonInput<KeyCode>>, mut boundary: ResMut<BoundaryMode>) {
+    if keyboard.just_pressed(KeyCode::KeyB) {
+        *boundary = boundary.next();
+        info!("Boid boundary mode: {:?}", *boundary);
+    }
+}
+
+/// Toggle flocking by kind with the K key
+fn toggle_kind_flocking(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<BoidSettings>) {
+    if keyboard.just_pressed(KeyCode::KeyK) {
+        settings.flock_by_kind = !settings.flock_by_kind;
+        info!("Flock by kind: {}", if settings.flock_by_kind { "on" } else { "off" });
+    }
+}
+
+/// Toggle overlap resolution with the O key
+fn toggle_overlap_resolution(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<BoidSettings>) {
+    if keyboard.just_pressed(KeyCode::KeyO) {
+        settings.prevent_overlap = !settings.prevent_overlap;
+        info!("Overlap resolution: {}", if settings.prevent_overlap { "on" } else { "off" });
     }
-}
\ No newline at end of file
+}
+
+/// Uniform grid over boid positions so flocking only scans nearby cells
+///
+/// Rebuilt at the start of `update_boids`, and again by `resolve_boid_overlap`
+/// after the boids have moved when that's enabled. With the cell size equal to the
+/// perception radius, a lookup covers the 3x3 block of cells around a boid.
+#[derive(Resource)]
+struct SpatialGrid {
+    cell_size: f32,                         // Side length of a cell in pixels
+    boids: Vec<(Vec2, Vec2, Entity, u8, BoidKind)>,  // Position, velocity, entity, team and kind of every boid
+    cells: HashMap<IVec2, Vec<usize>>,      // Indices into `boids` per cell
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self {
+            cell_size: 100.0,  // Matches the flocking perception radius
+            boids: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl SpatialGrid {
+    fn cell(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+    
+    /// Replace the grid contents with a fresh snapshot of boids
+    fn rebuild(&mut self, boids: impl Iterator<Item = (Vec2, Vec2, Entity, u8, BoidKind)>) {
+        self.boids.clear();
+        self.boids.extend(boids);
+        self.cells.clear();
+        for index in 0..self.boids.len() {
+            let cell = self.cell(self.boids[index].0);
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+    
+    /// Fill `out` with the indices of boids in cells that could be within `radius` of `pos`
+    ///
+    /// Indices come back in snapshot order, so neighbor sums are accumulated in the
+    /// same order as a scan over every boid would.
+    fn query(&self, pos: Vec2, radius: f32, out: &mut Vec<usize>) {
+        out.clear();
+        let center = self.cell(pos);
+        let reach = (radius / self.cell_size).ceil() as i32;
+        for x in -reach..=reach {
+            for y in -reach..=reach {
+                if let Some(indices) = self.cells.get(&(center + IVec2::new(x, y))) {
+                    out.extend_from_slice(indices);
+                }
+            }
+        }
+        out.sort_unstable();
+    }
+}
+
+/// Move boids that ended up inside each other apart after `update_boids`
+///
+/// A positional correction, not a force: separation only steers, so a dense flock
+/// still stacks triangles. Each boid of an overlapping pair moves back half the
+/// overlap. Every round works from the previous round's positions, so the result
+/// doesn't depend on iteration order; a few rounds settle boids overlapping several
+/// others at once.
+fn resolve_boid_overlap(
+    settings: Res<BoidSettings>,
+    mut grid: ResMut<SpatialGrid>,
+    mut boids: Query<(&Boid, &mut Transform, Entity, &BoidKind)>,
+) {
+    if !settings.prevent_overlap {
+        return;
+    }
+    
+    grid.rebuild(boids.iter().map(|(boid, transform, entity, kind)| {
+        (transform.translation.truncate(), boid.velocity, entity, boid.team, *kind)
+    }));
+    let min_distance = settings.body_radius * 2.0;
+    let rounds = 3;
+    let mut nearby = Vec::new();
+    let mut pushes = vec![Vec2::ZERO; grid.boids.len()];
+    
+    // Positions are corrected in the snapshot itself; the moves are far smaller
+    // than a cell, so the cell buckets stay good enough for the lookups
+    for _ in 0..rounds {
+        for (index, push) in pushes.iter_mut().enumerate() {
+            let pos = grid.boids[index].0;
+            grid.query(pos, min_distance, &mut nearby);
+            *push = Vec2::ZERO;
+            for &other in &nearby {
+                if other == index {
+                    continue;
+                }
+                let offset = pos - grid.boids[other].0;
+                let distance = offset.length();
+                if distance < min_distance {
+                    // Exactly stacked boids have no direction between them; split them sideways
+                    let direction = if distance > 0.0 {
+                        offset / distance
+                    } else if index < other {
+                        Vec2::X
+                    } else {
+                        Vec2::NEG_X
+                    };
+                    *push += direction * (min_distance - distance) / 2.0;
+                }
+            }
+        }
+        for (boid, push) in grid.boids.iter_mut().zip(&pushes) {
+            boid.0 += *push;
+        }
+    }
+    
+    for &(pos, _, entity, _, _) in &grid.boids {
+        if let Ok((_, mut transform, _, _)) = boids.get_mut(entity)
+            && transform.translation.truncate() != pos
+        {
+            transform.translation.x = pos.x;
+            transform.translation.y = pos.y;
+        }
+    }
+}
+
+/// Number of separate flocks, where boids within perception range of each other
+/// (directly or through a chain of neighbors) count as one flock
+///
+/// Recounted from the `SpatialGrid` snapshot on a timer rather than every frame.
+#[derive(Resource)]
+struct FlockClusters {
+    count: usize,
+    timer: Timer,           // Time between recounts
+    parents: Vec<usize>,    // Union-find forest over grid indices, reused between recounts
+    nearby: Vec<usize>,     // Grid query scratch buffer
+}
+
+impl Default for FlockClusters {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            timer: Timer::from_seconds(0.5, TimerMode::Repeating),
+            parents: Vec::new(),
+            nearby: Vec::new(),
+        }
+    }
+}
+
+/// Root of `index` in a union-find forest, halving the path on the way up
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+/// Recount flock clusters by joining every pair of boids within perception range
+fn count_flock_clusters(
+    mut clusters: ResMut<FlockClusters>,
+    grid: Res<SpatialGrid>,
+    settings: Res<BoidSettings>,
+    time: Res<Time>,
+) {
+    clusters.timer.tick(time.delta());
+    if !clusters.timer.just_finished() {
+        return;
+    }
+    
+    let radius = settings.perception_radius;
+    let FlockClusters { count, parents, nearby, .. } = &mut *clusters;
+    parents.clear();
+    parents.extend(0..grid.boids.len());
+    
+    for index in 0..grid.boids.len() {
+        let pos = grid.boids[index].0;
+        grid.query(pos, radius, nearby);
+        for &other in nearby.iter() {
+            // Each pair only needs joining once
+            if other <= index || grid.boids[other].0.distance(pos) >= radius {
+                continue;
+            }
+            let (root, other_root) = (find_root(parents, index), find_root(parents, other));
+            if root != other_root {
+                parents[other_root] = root;
+            }
+        }
+    }
+    
+    *count = (0..parents.len()).filter(|&index| find_root(parents, index) == index).count();
+}
+
+/// How boid energy drains while sprinting and limits top speed once spent
+#[derive(Resource)]
+struct Stamina {
+    sprint_speed: f32,      // Moving faster than this drains energy
+    drain_rate: f32,        // Energy lost per second while sprinting
+    regen_rate: f32,        // Energy regained per second while cruising
+    exhausted_speed: f32,   // Fraction of max speed left with no energy
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            sprint_speed: 220.0,
+            drain_rate: 0.25,   // About 4 seconds of sprinting from full
+            regen_rate: 0.1,    // About 10 seconds to recover fully
+            exhausted_speed: 0.6,
+        }
+    }
+}
+
+impl Stamina {
+    /// Top speed for a boid with the given energy, scaling from `exhausted_speed` up to `max_speed`
+    fn max_speed(&self, max_speed: f32, energy: f32) -> f32 {
+        max_speed * (self.exhausted_speed + (1.0 - self.exhausted_speed) * energy)
+    }
+}
+
+/// Color of a boid `progress` (0 to 1) of the way through its damage flash
+///
+/// The flash pulses five times towards bright red, either blending (`smooth`) or strobing.
+fn damage_flash_color(base_color: Color, progress: f32, smooth: bool) -> Color {
+    // Flash to bright red regardless of base color
+    let flash_color = Color::srgb(1.0, 0.0, 0.0);
+    let flash_intensity = (progress * 10.0 * std::f32::consts::PI).sin().abs();
+    
+    if smooth {
+        base_color.mix(&flash_color, flash_intensity)  // Pulsing glow
+    } else if flash_intensity > 0.5 {
+        flash_color  // Hard strobe
+    } else {
+        base_color
+    }
+}
+
+/// Rotate `new` back towards `previous` so the heading changes by at most `max_angle` radians
+///
+/// Only the direction is limited; the speed of `new` is kept as-is.
+fn clamp_turn(previous: Vec2, new: Vec2, max_angle: f32) -> Vec2 {
+    if previous == Vec2::ZERO || new == Vec2::ZERO {
+        return new;  // No heading to compare against
+    }
+    
+    let angle = previous.angle_to(new);
+    if angle.abs() <= max_angle {
+        return new;
+    }
+    
+    Vec2::from_angle(angle.clamp(-max_angle, max_angle)).rotate(previous.normalize()) * new.length()
+}
+
+/// Undamaged color of a boid, based on its kind and team
+fn boid_base_color(kind: BoidKind, boid: &Boid) -> Color {
+    match kind {
+        BoidKind::Pink => Color::srgb(1.0, 0.0, 0.5),
+        BoidKind::Red => Color::srgb(1.0, 0.2, 0.2),
+        BoidKind::Fast => Color::srgb(1.0, 0.9, 0.2),   // Yellow
+        BoidKind::Tank => Color::srgb(0.6, 0.4, 1.0),   // Violet
+        BoidKind::Splitter => Color::srgb(0.3, 1.0, 0.4),  // Green
+        BoidKind::Normal if boid.team == 1 => Color::srgb(0.4, 0.7, 1.0),  // Second team in light blue
+        BoidKind::Normal => Color::WHITE,  // Normal flock members
+    }
+}
+
+/// Create and update visual representations of boids (triangular meshes)
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn draw_boids(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    visual_settings: Res<VisualSettings>,
+    render_style: Res<RenderStyle>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    boids: Query<(Entity, &Boid, &BoidKind, Has<LowDetail>), Without<BoidVisual>>,  // Boids without visuals
+    mut visuals: Query<(&mut Transform, &ChildOf, Option<&MeshMaterial2d<ColorMaterial>>, Option<&mut Sprite>), With<BoidVisual>>,
+    predators: Query<Entity, Added<Predator>>,
+    leaders: Query<Entity, Added<Leader>>,
+) {
+    // Predators are a larger orange triangle; their heading is the parent's rotation
+    for entity in &predators {
+        commands.entity(entity).with_child((
+            Mesh2d(visual_assets.boid_mesh.clone()),
+            MeshMaterial2d(visual_assets.predator_material.clone()),
+            Transform::from_scale(Vec3::splat(2.5)),
+        ));
+    }
+    
+    // The leader is a gold triangle, between a boid and the predator in size
+    for entity in &leaders {
+        commands.entity(entity).with_child((
+            Mesh2d(visual_assets.boid_mesh.clone()),
+            MeshMaterial2d(visual_assets.leader_material.clone()),
+            Transform::from_scale(Vec3::splat(1.8)),
+        ));
+    }
+    
+    // Create visual representations for boids that don't have them yet
+    for (entity, boid, kind, _) in &boids {
+        // Check if this boid already has a visual child
+        if visuals.iter().find(|(_, child_of, _, _)| child_of.parent() == entity).is_none() {
+            let base_color = boid_base_color(*kind, boid);
+            
+            let visual = match *render_style {
+                RenderStyle::Triangle => {
+                    // Each boid gets its own material since its tint tracks its own health
+                    let material = materials.add(ColorMaterial::from(base_color));
+                    commands
+                        .spawn((
+                            Mesh2d(visual_assets.boid_mesh.clone()),
+                            MeshMaterial2d(material),
+                            Transform::from_scale(Vec3::splat(1.0)),
+                            BoidVisual,
+                        ))
+                        .id()
+                }
+                RenderStyle::Sprite => {
+                    // A little larger than the triangle: the arrow's notch takes up some of it
+                    let size = Vec2::new(visual_settings.boid_width, visual_settings.boid_length) * 1.5;
+                    commands
+                        .spawn((
+                            Sprite {
+                                image: visual_assets.boid_texture.clone(),
+                                color: base_color,
+                                custom_size: Some(size),
+                                ..default()
+                            },
+                            Transform::from_scale(Vec3::splat(1.0)),
+                            BoidVisual,
+                        ))
+                        .id()
+                }
+            };
+            
+            // Health bar stays level above the boid: the parent itself never rotates
+            let health_bar = commands
+                .spawn((
+                    Mesh2d(visual_assets.health_bar_mesh.clone()),
+                    MeshMaterial2d(visual_assets.health_bar_material.clone()),
+                    Transform::from_xyz(0.0, 10.0, 0.1),
+                    Visibility::Hidden,  // Only shown once damaged
+                    HealthBar,
+                ))
+                .id();
+            
+            // Make visual a child of the boid entity
+            commands.entity(entity).add_children(&[visual, health_bar]);
+        }
+    }
+    
+    // Update existing visual representations
+    for (mut visual_transform, child_of, material_handle, sprite) in &mut visuals {
+        if let Ok((_, boid, kind, low_detail)) = boids.get(child_of.parent()) {
+            // Update rotation to point in movement direction
+            let angle = boid.velocity.y.atan2(boid.velocity.x) - std::f32::consts::FRAC_PI_2;
+            visual_transform.rotation = Quat::from_rotation_z(angle);
+            
+            // Distant boids are drawn smaller when zoomed out
+            // Split-off halves shrink with each generation
+            let size = 0.75_f32.powi(boid.generation as i32);
+            visual_transform.scale = Vec3::splat(size * if low_detail { 0.6 } else { 1.0 });
+            
+            // Update color based on health and damage state
+            let base_color = boid_base_color(*kind, boid);
+            let color = if !low_detail && !boid.damage_flash_timer.finished() {
+                // Apply damage flash effect if timer is active (skipped for low-detail boids)
+                let flash_progress = boid.damage_flash_timer.elapsed_secs() / boid.damage_flash_timer.duration().as_secs_f32();
+                damage_flash_color(base_color, flash_progress, visual_settings.smooth_damage_flash)
+            } else if boid.health < boid.max_health {
+                // Show damage by darkening the color based on health
+                let health_factor = boid.health / boid.max_health;
+                let srgba = base_color.to_srgba();
+                Color::srgb(
+                    srgba.red * health_factor,
+                    srgba.green * health_factor,
+                    srgba.blue * health_factor,
+                )
+            } else {
+                // Full health - use normal base color
+                base_color
+            };
+            
+            // Triangles carry the tint in their material, sprites in their color
+            if let Some(material_handle) = material_handle
+                && let Some(material) = materials.get_mut(&material_handle.0)
+            {
+                material.color = color;
+            }
+            if let Some(mut sprite) = sprite
+                && sprite.color != color
+            {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+/// Scale each health bar to its boid's health, hiding it while undamaged
+fn update_health_bars(
+    mut bars: Query<(&mut Transform, &mut Visibility, &ChildOf), With<HealthBar>>,
+    boids: Query<&Boid>,
+) {
+    let full_width = 12.0;  // Bar width at full health
+    let height = 2.0;
+    
+    for (mut transform, mut visibility, child_of) in &mut bars {
+        let Ok(boid) = boids.get(child_of.parent()) else { continue; };
+        
+        let wanted = if boid.health < boid.max_health { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        transform.scale = Vec3::new(full_width * (boid.health / boid.max_health).max(0.0), height, 1.0);
+    }
+}
+
+/// Flag boids far from the camera center as `LowDetail` once the camera is zoomed out
+fn update_boid_lod(
+    mut commands: Commands,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    boids: Query<(Entity, &Transform, Has<LowDetail>), With<Boid>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.single() else { return; };
+    
+    let lod_scale = 1.5;        // Projection scale (zoom-out) at which LOD kicks in
+    let lod_distance = 800.0;   // World distance from camera center beyond which boids simplify
+    
+    let scale = match projection {
+        Projection::Orthographic(orthographic) => orthographic.scale,
+        _ => 1.0,
+    };
+    let camera_pos = camera_transform.translation.truncate();
+    
+    for (entity, transform, is_low_detail) in &boids {
+        let should_be_low_detail = scale >= lod_scale
+            && transform.translation.truncate().distance(camera_pos) > lod_distance;
+        
+        // Only touch the component when the LOD level actually changes
+        if should_be_low_detail && !is_low_detail {
+            commands.entity(entity).try_insert(LowDetail);
+        } else if !should_be_low_detail && is_low_detail {
+            commands.entity(entity).try_remove::<LowDetail>();
+        }
+    }
+}
+
+// ===== TURRET SYSTEMS =====
+
+/// How far off the target direction (radians) a barrel may point and still fire
+const AIM_TOLERANCE: f32 = 0.1;
+
+/// Closest two emplacements may be, center to center (wider than any base mesh)
+const MIN_TURRET_SPACING: f32 = 25.0;
+
+/// Price of a laser turret built by the player
+const TURRET_COST: u32 = 50;
+
+/// Spawn a turret with targeting logic (meshes are attached by `attach_turret_visuals`)
+///
+/// `projectile_speed` of `None` makes a laser turret; `Some` fires homing rounds at that speed.
+fn spawn_turret(
+    commands: &mut Commands,
+    position: Vec2,
+    id: TurretId,
+    targeting: TargetingMode,
+    max_targets: usize,
+    projectile_speed: Option<f32>,
+) -> Entity {
+    let entity = commands.spawn((
+        Transform::from_translation(position.extend(-1.0)),  // Behind boids in Z-order
+        Turret {
+            targets: Vec::new(),                             // No initial targets
+            max_targets,
+            range: 250.0,                                   // Targeting range
+            cooldown_timer: Timer::from_seconds(0.5, TimerMode::Once),  // Target acquisition delay
+            targeting,
+            spin_up_timer: Timer::from_seconds(0.75, TimerMode::Once),  // Damage ramp-up after retargeting
+            prefer_approaching: false,
+            fire_timer: Timer::from_seconds(0.5, TimerMode::Once),  // Two shots per second
+            shot_damage: 0.25,                               // Four full-power shots per boid
+            projectile_speed,
+            level: 1,
+            aim_angle: std::f32::consts::FRAC_PI_2,          // Barrel starts pointing up
+            turn_speed: 4.0,                                 // About 1.5 s for a full turn
+            on_target: false,
+            rotate_base: projectile_speed.is_some(),         // Launchers turn on their base, lasers swivel the barrel
+            heat: 0.0,
+            heat_per_shot: 0.2,                              // Overheats after about 5 s of continuous fire
+            cooling_rate: 0.2,                               // About 3 s locked out once overheated
+            overheated: false,
+        },
+        id,
+        TurretStats::default(),
+    )).id();
+    commands.trigger(TurretPlaced { position });
+    entity
+}
+
+/// Create defensive turrets at strategic positions around the map
+fn setup_turrets(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+) {
+    let playfield = bounds.size;
+    
+    // Strategic turret positions for good map coverage
+    let positions = vec![
+        (Vec2::new(-playfield.x / 3.0, -playfield.y / 3.0), TargetingMode::Closest, 1, None),  // Bottom left
+        (Vec2::new(playfield.x / 3.0, -playfield.y / 3.0), TargetingMode::Closest, 1, Some(400.0)),  // Bottom right fires homing rounds
+        (Vec2::new(0.0, playfield.y / 3.0), TargetingMode::Threat, 3, None),                   // Top center guards the others, three beams at once
+        (Vec2::new(-playfield.x / 4.0, playfield.y / 4.0), TargetingMode::Closest, 1, None),   // Top left
+        (Vec2::new(playfield.x / 4.0, playfield.y / 4.0), TargetingMode::Closest, 1, None),    // Top right
+    ];
+    
+    // Skip any preset that would overlap one already placed
+    let mut placed: Vec<Vec2> = Vec::new();
+    let too_close = |placed: &[Vec2], pos: Vec2| placed.iter().any(|other| other.distance(pos) < MIN_TURRET_SPACING);
+    for (pos, targeting, max_targets, projectile_speed) in positions {
+        if too_close(&placed, pos) {
+            warn!("Skipping turret at {:.0}: within {} px of another turret", pos, MIN_TURRET_SPACING);
+            continue;
+        }
+        placed.push(pos);
+        spawn_turret(&mut commands, pos, TurretId(placed.len() as u32), targeting, max_targets, projectile_speed);
+    }
+    
+    // Auto-artillery emplacement at bottom center, numbered after the laser turrets
+    let artillery_pos = Vec2::new(0.0, -playfield.y / 3.0);
+    if too_close(&placed, artillery_pos) {
+        warn!("Skipping artillery at {:.0}: within {} px of a turret", artillery_pos, MIN_TURRET_SPACING);
+        return;
+    }
+    spawn_artillery(&mut commands, artillery_pos, TurretId(placed.len() as u32 + 1));
+}
+
+/// Spawn an auto-artillery emplacement (meshes are attached by `attach_turret_visuals`)
+fn spawn_artillery(commands: &mut Commands, position: Vec2, id: TurretId) -> Entity {
+    let entity = commands.spawn((
+        Transform::from_translation(position.extend(-1.0)),
+        Artillery {
+            range: 350.0,
+            density_radius: 60.0,
+            density_threshold: 8,
+            splash_radius: 60.0,
+            splash_damage: 0.6,
+            reload_timer: Timer::from_seconds(2.0, TimerMode::Once),
+        },
+        id,
+        TurretStats::default(),
+    )).id();
+    commands.trigger(TurretPlaced { position });
+    entity
+}
+
+/// Give newly spawned turrets and artillery their meshes
+fn attach_turret_visuals(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    turrets: Query<Entity, Added<Turret>>,
+    artillery: Query<Entity, Added<Artillery>>,
+    obstacles: Query<(Entity, &Obstacle), Added<Obstacle>>,
+    projectiles: Query<Entity, Added<Projectile>>,
+) {
+    // A round can hit on its first frame, so it may already be gone
+    for entity in &projectiles {
+        commands.entity(entity).try_insert((
+            Mesh2d(visual_assets.unit_circle_mesh.clone()),
+            MeshMaterial2d(visual_assets.projectile_material.clone()),
+        ));
+    }
+    
+    for entity in &turrets {
+        commands
+            .entity(entity)
+            .insert((
+                Mesh2d(visual_assets.turret_base_mesh.clone()),
+                MeshMaterial2d(visual_assets.turret_material.clone()),
+            ))
+            .with_children(|parent| {
+                // Spawn turret barrel as child (rotates with targeting)
+                parent.spawn((
+                    Mesh2d(visual_assets.turret_barrel_mesh.clone()),
+                    MeshMaterial2d(visual_assets.barrel_heat_materials[0].clone()),  // Cold
+                    Transform::from_xyz(0.0, 10.0, 0.1),  // Offset forward from base
+                    TurretBarrel,
+                ));
+                parent.spawn(kill_label());
+            });
+    }
+    
+    for entity in &artillery {
+        commands
+            .entity(entity)
+            .insert((
+                Mesh2d(visual_assets.artillery_base_mesh.clone()),
+                MeshMaterial2d(visual_assets.artillery_material.clone()),
+            ))
+            .with_child(kill_label());
+    }
+    
+    // Obstacles are a child circle so the logic entity's scale stays untouched
+    for (entity, obstacle) in &obstacles {
+        commands.entity(entity).with_child((
+            Mesh2d(visual_assets.unit_circle_mesh.clone()),
+            MeshMaterial2d(visual_assets.obstacle_material.clone()),
+            Transform::from_scale(Vec3::splat(obstacle.radius)),
+        ));
+    }
+}
+
+/// Kill count shown above a turret or artillery emplacement
+#[derive(Component)]
+struct KillLabel;
+
+/// Offset of the kill count above the emplacement center
+const KILL_LABEL_OFFSET: Vec3 = Vec3::new(0.0, 22.0, 0.2);
+
+fn kill_label() -> impl Bundle {
+    (
+        Text2d::new("0"),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        Transform::from_translation(KILL_LABEL_OFFSET),
+        KillLabel,
+    )
+}
+
+/// Refresh kill counts when they change, keeping each label upright above its turret
+///
+/// Turrets with `rotate_base` turn the whole emplacement, so the label undoes that rotation.
+#[allow(clippy::type_complexity)]
+fn update_kill_labels(
+    emplacements: Query<(&TurretStats, &Transform, &Children), Or<(Changed<TurretStats>, Changed<Transform>)>>,
+    mut labels: Query<(&mut Text2d, &mut Transform), (With<KillLabel>, Without<TurretStats>)>,
+) {
+    for (stats, transform, children) in &emplacements {
+        let upright = transform.rotation.inverse();
+        for child in children.iter() {
+            let Ok((mut text, mut label_transform)) = labels.get_mut(child) else { continue; };
+            
+            let count = stats.kills.to_string();
+            if text.0 != count {
+                text.0 = count;
+            }
+            let wanted = Transform::from_translation(upright * KILL_LABEL_OFFSET).with_rotation(upright);
+            if *label_transform != wanted {
+                *label_transform = wanted;
+            }
+        }
+    }
+}
+
+/// Shade each turret's barrel from the plain turret color towards red as it heats up
+fn tint_hot_barrels(
+    turrets: Query<&Turret>,
+    mut barrels: Query<(&ChildOf, &mut MeshMaterial2d<ColorMaterial>), With<TurretBarrel>>,
+    visual_assets: Res<VisualAssets>,
+) {
+    let ramp = &visual_assets.barrel_heat_materials;
+    for (child_of, mut material) in &mut barrels {
+        let Ok(turret) = turrets.get(child_of.parent()) else { continue; };
+        let heat = (turret.heat / Turret::OVERHEAT).min(1.0);
+        let wanted = &ramp[(heat * (ramp.len() - 1) as f32).round() as usize];
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
+
+/// Target acquisition delay for a turret with `in_range` boids inside its range
+///
+/// Scales inversely with the number of candidates, clamped so a swarm can't make
+/// turrets retarget instantly and an empty field doesn't stall them for long.
+fn adaptive_cooldown(in_range: usize) -> f32 {
+    let min_cooldown = 0.1;  // Fastest reacquisition (seconds) mid-swarm
+    let max_cooldown = 1.0;  // Slowest reacquisition (seconds) when sparse
+    (max_cooldown / in_range.max(1) as f32).clamp(min_cooldown, max_cooldown)
+}
+
+/// Update turret targeting logic
+#[allow(clippy::type_complexity)]
+fn update_turrets(
+    mut turrets: Query<(&mut Turret, &mut Transform, Option<&Children>)>,
+    mut barrels: Query<&mut Transform, (With<TurretBarrel>, Without<Turret>, Without<Boid>)>,
+    boids: Query<(&Transform, &Boid, Entity, &BoidKind), Without<Turret>>,
+    grid: Res<SpatialGrid>,
+    zone: Res<ProtectZone>,
+    time: Res<Time>,
+) {
+    let mut nearby = Vec::new();
+    
+    // Turret positions for threat assessment (boids closing in on any turret)
+    let turret_positions: Vec<Vec2> = turrets
+        .iter()
+        .map(|(_, transform, _)| transform.translation.truncate())
+        .collect();
+
+    // Boids closing in on what they threaten are prioritised: each px/s of
+    // closing speed counts as this many px closer when ranking targets
+    let closing_weight = 0.5;
+    
+    for (mut turret, mut turret_transform, children) in &mut turrets {
+        // Update targeting cooldown and damage spin-up timers
+        turret.cooldown_timer.tick(time.delta());
+        turret.spin_up_timer.tick(time.delta());
+        
+        // Barrels cool continuously; an overheated turret rejoins once it's cool enough
+        turret.heat = (turret.heat - turret.cooling_rate * time.delta_secs()).max(0.0);
+        if turret.overheated && turret.heat <= Turret::RESUME_HEAT {
+            turret.overheated = false;
+        }
+        
+        // ===== TARGET VALIDATION =====
+        // Drop targets that died or left range, keeping the rest
+        let turret_pos = turret_transform.translation.truncate();
+        let range = turret.range;
+        let tracked = turret.targets.len();
+        turret.targets.retain(|&target_entity| {
+            boids
+                .get(target_entity)
+                .is_ok_and(|(boid_transform, _, _, _)| turret_pos.distance(boid_transform.translation.truncate()) < range)
+        });
+        
+        // If a target is lost, start cooldown before filling its slot
+        if turret.targets.len() < tracked {
+            // Reacquire faster when the area is target-rich, slower when it's sparse
+            grid.query(turret_pos, turret.range, &mut nearby);
+            let in_range = nearby
+                .iter()
+                .filter_map(|&index| boids.get(grid.boids[index].2).ok())
+                .filter(|(boid_transform, _, _, _)| turret_pos.distance(boid_transform.translation.truncate()) < turret.range)
+                .count();
+            turret.cooldown_timer.set_duration(Duration::from_secs_f32(adaptive_cooldown(in_range)));
+            turret.cooldown_timer.reset();
+        }
+        
+        // ===== TARGET ACQUISITION =====
+        // Fill free target slots only after cooldown expires
+        if turret.targets.len() < turret.max_targets && turret.cooldown_timer.finished() {
+            // Score every untracked boid within range (lowest score is most urgent);
+            // the grid only narrows the search to cells the range reaches
+            let mut candidates: Vec<(f32, Entity)> = Vec::new();
+            grid.query(turret_pos, turret.range, &mut nearby);
+            for &index in &nearby {
+                let Ok((boid_transform, boid, boid_entity, kind)) = boids.get(grid.boids[index].2) else { continue; };
+                let boid_pos = boid_transform.translation.truncate();
+                let distance = turret_pos.distance(boid_pos);
+                if distance >= turret.range || turret.targets.contains(&boid_entity) {
+                    continue;
+                }
+                
+                let score = match turret.targeting {
+                    TargetingMode::Closest => {
+                        // Closest, weighted towards boids heading for the protected zone
+                        let closing_speed = boid.velocity.dot((zone.center - boid_pos).normalize_or_zero());
+                        distance - closing_speed.max(0.0) * closing_weight
+                    }
+                    TargetingMode::Threat => {
+                        // Proximity to the nearest turret, weighted by closing speed towards it
+                        let nearest_turret = turret_positions
+                            .iter()
+                            .copied()
+                            .min_by(|a, b| a.distance(boid_pos).total_cmp(&b.distance(boid_pos)))
+                            .unwrap_or(turret_pos);
+                        let closing_speed = boid.velocity.dot((nearest_turret - boid_pos).normalize_or_zero());
+                        nearest_turret.distance(boid_pos) - closing_speed.max(0.0) * closing_weight
+                    }
+                    // Ties (e.g. every boid unhurt) go to the closest boid
+                    TargetingMode::LowestHealth => boid.health * turret.range + distance,
+                    TargetingMode::HighestValue => -Score::kill_value(*kind) * turret.range + distance,
+                    TargetingMode::FirstInZone => {
+                        let to_zone = zone.center - boid_pos;
+                        let closing_speed = boid.velocity.dot(to_zone.normalize_or_zero());
+                        to_zone.length() - closing_speed.max(0.0) * closing_weight
+                    }
+                };
+                
+                // Boids already leaving tend to exit range before dying, so only take
+                // them when nothing in range is approaching
+                let approach_speed = boid.velocity.dot((turret_pos - boid_pos).normalize_or_zero());
+                let score = if turret.prefer_approaching && approach_speed < 0.0 {
+                    score + turret.range * 2.0  // Worse than any approaching boid's score
+                } else {
+                    score
+                };
+                candidates.push((score, boid_entity));
+            }
+            
+            candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let was_idle = turret.targets.is_empty();
+            let free_slots = turret.max_targets - turret.targets.len();
+            turret.targets.extend(candidates.into_iter().take(free_slots).map(|(_, entity)| entity));
+            
+            if was_idle && !turret.targets.is_empty() {
+                turret.spin_up_timer.reset();  // New engagement: damage starts low again
+            }
+        }
+        
+        // ===== BARREL AIMING =====
+        // Swing the barrel towards where the shot will meet the primary target,
+        // no faster than the turret's turn speed
+        turret.on_target = false;
+        if let Some(&target_entity) = turret.targets.first()
+            && let Ok((boid_transform, boid, _, _)) = boids.get(target_entity)
+        {
+            let boid_pos = boid_transform.translation.truncate();
+            let aim = turret
+                .projectile_speed
+                .and_then(|speed| intercept_point(turret_pos, boid_pos, boid.velocity, speed))
+                .unwrap_or(boid_pos);  // Instant shots, or no way to catch it: aim straight
+            
+            let offset = aim - turret_pos;
+            if offset != Vec2::ZERO {
+                let max_step = turret.turn_speed * time.delta_secs();
+                let remaining = angle_between(turret.aim_angle, offset.to_angle());
+                let step = remaining.clamp(-max_step, max_step);
+                turret.aim_angle = (turret.aim_angle + step).rem_euclid(std::f32::consts::TAU);
+                turret.on_target = (remaining - step).abs() <= AIM_TOLERANCE;
+            }
+        }
+        
+        // Either the whole turret turns and the barrel stays pointing forward on it,
+        // or the base stays square and the barrel swings around it
+        let rotation = Quat::from_rotation_z(turret.aim_angle - std::f32::consts::FRAC_PI_2);
+        let (base_rotation, barrel_rotation) = if turret.rotate_base {
+            (rotation, Quat::IDENTITY)
+        } else {
+            (Quat::IDENTITY, rotation)
+        };
+        if turret_transform.rotation != base_rotation {
+            turret_transform.rotation = base_rotation;
+        }
+        if let Some(children) = children {
+            for child in children.iter() {
+                if let Ok(mut barrel_transform) = barrels.get_mut(child)
+                    && barrel_transform.rotation != barrel_rotation
+                {
+                    // Swing the barrel around the base center, not its own
+                    barrel_transform.translation = (barrel_rotation * Vec3::new(0.0, 10.0, 0.0)).with_z(0.1);
+                    barrel_transform.rotation = barrel_rotation;
+                }
+            }
+        }
+    }
+}
+
+/// Signed shortest rotation from angle `from` to angle `to`, in (-π, π]
+fn angle_between(from: f32, to: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    let difference = (to - from).rem_euclid(tau);
+    if difference > std::f32::consts::PI { difference - tau } else { difference }
+}
+
+/// Point where a shot fired now at `speed` meets a target moving at constant `target_velocity`
+///
+/// Solves |offset + v·t| = speed·t for the earliest positive t. Returns `None` when
+/// the target outruns the shot.
+fn intercept_point(shooter: Vec2, target: Vec2, target_velocity: Vec2, speed: f32) -> Option<Vec2> {
+    let offset = target - shooter;
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+    
+    let time = if a.abs() < 1e-6 {
+        // Target exactly as fast as the shot: the equation is linear
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+        let earliest = t1.min(t2);
+        if earliest > 0.0 { earliest } else { t1.max(t2) }
+    };
+    
+    (time > 0.0).then(|| target + target_velocity * time)
+}
+
+/// Transform stretching the shared unit-length laser mesh from `from` to `to`
+fn beam_transform(from: Vec2, to: Vec2) -> Transform {
+    let direction = to - from;
+    let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
+    
+    // Position laser at midpoint between turret and target
+    Transform::from_translation((from + direction / 2.0).extend(-1.0))
+        .with_rotation(Quat::from_rotation_z(angle))
+        .with_scale(Vec3::new(1.0, direction.length(), 1.0))
+}
+
+/// Draw a short laser flash from a turret to the boid it just shot
+fn spawn_laser_flash(
+    trigger: Trigger<TurretFired>,
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    laser_settings: Res<LaserSettings>,
+    lasers: Query<(), With<LaserBeam>>,
+) {
+    // Past the cap turrets keep damaging without drawing a beam
+    if lasers.iter().count() >= laser_settings.max_rendered_beams {
+        return;
+    }
+    
+    let shot = trigger.event();
+    commands.spawn((
+        Mesh2d(visual_assets.laser_mesh.clone()),
+        MeshMaterial2d(visual_assets.laser_material.clone()),
+        beam_transform(shot.from, shot.to),
+        LaserBeam {
+            turret: shot.turret,
+            target: shot.target,
+            lifetime: Timer::from_seconds(laser_settings.flash_duration, TimerMode::Once),
+        },
+    ));
+}
+
+/// Keep laser flashes stretched between turret and target until they expire
+///
+/// Flashes are purely visual: damage is applied by `apply_laser_damage` when the shot fires.
+fn update_lasers(
+    mut commands: Commands,
+    mut lasers: Query<(Entity, &mut LaserBeam, &mut Transform)>,
+    turrets: Query<(&Turret, &Transform), Without<LaserBeam>>,
+    endpoints: Query<&Transform, Without<LaserBeam>>,
+    time: Res<Time>,
+) {
+    for (laser_entity, mut laser_beam, mut laser_transform) in &mut lasers {
+        laser_beam.lifetime.tick(time.delta());
+        
+        // Remove expired flashes and those whose turret was sold
+        let Ok((turret, turret_transform)) = turrets.get(laser_beam.turret) else {
+            commands.entity(laser_entity).despawn();
+            continue;
+        };
+        if laser_beam.lifetime.finished() {
+            commands.entity(laser_entity).despawn();
+            continue;
+        }
+        
+        // Follow the target while it lives; a killed boid leaves the flash where it died
+        if let Ok(boid_transform) = endpoints.get(laser_beam.target) {
+            *laser_transform = beam_transform(
+                turret.muzzle(turret_transform.translation.truncate()),
+                boid_transform.translation.truncate(),
+            );
+        }
+    }
+}
+
+/// Fire turrets whose next shot is ready at each of their current targets
+fn apply_laser_damage(
+    mut commands: Commands,
+    mut turrets: Query<(Entity, &mut Turret, &TurretId, &mut TurretStats, &Transform)>,
+    mut boids: Query<(Entity, &mut Boid, &BoidId, &BoidKind, &Transform)>,
+    resistances: Res<Resistances>,
+    mut score: ResMut<Score>,
+    mut next_id: ResMut<NextBoidId>,
+    time: Res<Time>,
+) {
+    for (turret_entity, mut turret, turret_id, mut stats, turret_transform) in &mut turrets {
+        turret.fire_timer.tick(time.delta());
+        if !turret.fire_timer.finished() {
+            continue;  // Still recharging
+        }
+        if !turret.on_target {
+            continue;  // Hold fire while the barrel swings round (the whole volley follows the primary target)
+        }
+        if turret.overheated {
+            continue;  // Cooling down
+        }
+        
+        // Damage ramps from 20% to full while the turret spins up on a new target
+        let spin_up = 0.2 + 0.8 * turret.spin_up_timer.fraction();
+        let turret_pos = turret_transform.translation.truncate();
+        let mut fired = false;
+        
+        // One shot at every tracked target
+        for &target_entity in &turret.targets {
+            let Ok((boid_entity, mut boid, boid_id, kind, boid_transform)) = boids.get_mut(target_entity) else { continue; };
+            
+            // Skip boids already killed by another turret this frame (despawn is deferred)
+            if boid.health <= 0.0 {
+                continue;
+            }
+            
+            // Verify target is still in range
+            let boid_pos = boid_transform.translation.truncate();
+            if turret_pos.distance(boid_pos) > turret.range {
+                continue;
+            }
+            
+            // Projectile turrets launch a homing round instead; it deals damage on contact
+            if let Some(speed) = turret.projectile_speed {
+                let aim = intercept_point(turret_pos, boid_pos, boid.velocity, speed).unwrap_or(boid_pos);
+                commands.spawn((
+                    Projectile {
+                        velocity: (aim - turret_pos).normalize_or_zero() * speed,
+                        damage: turret.shot_damage * turret.damage_multiplier() * spin_up,
+                        target: boid_entity,
+                        turret: turret_entity,
+                        turret_id: *turret_id,
+                        lifetime: Timer::from_seconds(2.0, TimerMode::Once),
+                    },
+                    Transform::from_translation(turret.muzzle(turret_pos).extend(0.3))  // Above boids
+                        .with_scale(Vec3::splat(3.0)),                    // Drawn as a radius-3 dot
+                ));
+                stats.shots_fired += 1;
+                fired = true;
+                continue;
+            }
+            
+            // Apply the shot (only the health actually removed counts as dealt)
+            let multiplier = resistances.multiplier(DamageType::Laser, *kind);
+            let damage = (turret.shot_damage * turret.damage_multiplier() * spin_up * multiplier).min(boid.health);
+            boid.health -= damage;
+            stats.damage_dealt += damage;
+            stats.shots_fired += 1;
+            fired = true;
+            commands.trigger(TurretFired {
+                turret: turret_entity,
+                target: boid_entity,
+                from: turret.muzzle(turret_pos),
+                to: boid_pos,
+            });
+            
+            // Trigger damage flash effect
+            if boid.damage_flash_timer.finished() {
+                boid.damage_flash_timer = Timer::from_seconds(0.5, TimerMode::Once);
+            }
+            
+            // Destroy boid when health is depleted
+            if boid.health <= 0.0 {
+                let victim = (boid_entity, &*boid, boid_id, kind, boid_transform);
+                kill_boid(&mut commands, &mut score, &mut next_id, Some(&mut stats), *turret_id, victim);
+            }
+        }
+        
+        if fired {
+            turret.fire_timer.reset();
+            turret.heat += turret.heat_per_shot;
+            if turret.heat >= Turret::OVERHEAT {
+                turret.overheated = true;
+                info!("Turret #{} overheated", turret_id.0);
+            }
+        }
+    }
+}
+
+/// Remove a boid whose health just ran out to fire from turret `killer`
+///
+/// Every weapon kills through here, so a kill always counts towards the turret's
+/// stats and the score, splits a splitter, and fires both `BoidDespawned` and
+/// `BoidKilled`. `stats` is `None` when the turret is already gone.
+fn kill_boid(
+    commands: &mut Commands,
+    score: &mut Score,
+    next_id: &mut NextBoidId,
+    stats: Option<&mut TurretStats>,
+    killer: TurretId,
+    (entity, boid, id, kind, transform): (Entity, &Boid, &BoidId, &BoidKind, &Transform),
+) {
+    let position = transform.translation.truncate();
+    if let Some(stats) = stats {
+        stats.kills += 1;
+    }
+    score.add_kill(*kind);
+    if *kind == BoidKind::Splitter {
+        split_boid(commands, next_id, boid, position);
+    }
+    commands.entity(entity).despawn();
+    commands.trigger(BoidDespawned {
+        entity,
+        id: *id,
+        kind: *kind,
+        cause: DespawnCause::KilledByTurret(killer),
+    });
+    commands.trigger(BoidKilled {
+        position,
+        color: boid_base_color(*kind, boid),
+    });
+}
+
+/// Homing round fired by a projectile turret
+///
+/// Steers after `target` and damages the first boid it touches; once the target
+/// is gone it flies straight until its lifetime runs out.
+#[derive(Component)]
+struct Projectile {
+    velocity: Vec2,        // Current heading and speed
+    damage: f32,           // Health removed on hit, before resistances
+    target: Entity,        // Boid the round homes in on
+    turret: Entity,        // Turret credited with the damage and any kill
+    turret_id: TurretId,
+    lifetime: Timer,       // Despawns when this runs out without a hit
+}
+
+/// Move homing rounds, damage the first boid each one touches and expire misses
+#[allow(clippy::too_many_arguments)]
+fn update_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Projectile, &mut Transform), Without<Boid>>,
+    mut boids: Query<(Entity, &mut Boid, &BoidId, &BoidKind, &Transform)>,
+    mut turret_stats: Query<&mut TurretStats>,
+    grid: Res<SpatialGrid>,
+    resistances: Res<Resistances>,
+    mut score: ResMut<Score>,
+    mut next_id: ResMut<NextBoidId>,
+    time: Res<Time>,
+) {
+    let hit_radius = 8.0;   // Contact distance between round and boid center
+    let turn_rate = 6.0;    // Fastest the round can swing towards its target (radians per second)
+    let mut nearby = Vec::new();
+    
+    for (entity, mut projectile, mut transform) in &mut projectiles {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.finished() {
+            commands.entity(entity).despawn();  // Missed
+            continue;
+        }
+        
+        // Home in on the target while it lives
+        let pos = transform.translation.truncate();
+        if let Ok((_, _, _, _, target_transform)) = boids.get(projectile.target) {
+            let desired = (target_transform.translation.truncate() - pos).normalize_or_zero() * projectile.velocity.length();
+            projectile.velocity = clamp_turn(projectile.velocity, desired, turn_rate * time.delta_secs());
+        }
+        let pos = pos + projectile.velocity * time.delta_secs();
+        transform.translation = pos.extend(transform.translation.z);
+        
+        // Hit the first live boid in contact (the grid only narrows the search)
+        grid.query(pos, hit_radius, &mut nearby);
+        let hit = nearby.iter().find_map(|&index| {
+            let (_, _, boid_entity, _, _) = grid.boids[index];
+            let (_, boid, _, _, boid_transform) = boids.get(boid_entity).ok()?;
+            (boid.health > 0.0 && boid_transform.translation.truncate().distance(pos) < hit_radius).then_some(boid_entity)
+        });
+        let Some(hit) = hit else { continue; };
+        let Ok((boid_entity, mut boid, boid_id, kind, boid_transform)) = boids.get_mut(hit) else { continue; };
+        commands.entity(entity).despawn();
+        
+        // Apply the hit (only the health actually removed counts as dealt)
+        let multiplier = resistances.multiplier(DamageType::Kinetic, *kind);
+        let damage = (projectile.damage * multiplier).min(boid.health);
+        boid.health -= damage;
+        if boid.damage_flash_timer.finished() {
+            boid.damage_flash_timer = Timer::from_seconds(0.5, TimerMode::Once);
+        }
+        let mut stats = turret_stats.get_mut(projectile.turret).ok();
+        if let Some(stats) = stats.as_mut() {
+            stats.damage_dealt += damage;
+        }
+        
+        // Destroy boid when health is depleted
+        if boid.health <= 0.0 {
+            let victim = (boid_entity, &*boid, boid_id, kind, boid_transform);
+            kill_boid(&mut commands, &mut score, &mut next_id, stats.as_deref_mut(), projectile.turret_id, victim);
+        }
+    }
+}
+
+/// Static circular obstacle boids steer around
+#[derive(Component)]
+struct Obstacle {
+    radius: f32,
+}
+
+/// Place a few obstacles in the open ground between the turrets
+fn setup_obstacles(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+) {
+    let playfield = bounds.size;
+    
+    let obstacles = [
+        (Vec2::new(-playfield.x / 6.0, -playfield.y / 8.0), 40.0),  // Lower left of the zone
+        (Vec2::new(playfield.x / 6.0, -playfield.y / 8.0), 40.0),   // Lower right of the zone
+        (Vec2::new(0.0, playfield.y / 6.0), 50.0),                  // Above the zone
+    ];
+    for (position, radius) in obstacles {
+        commands.spawn((
+            Obstacle { radius },
+            Transform::from_translation(position.extend(-1.5)),  // Behind turrets and boids
+            Visibility::default(),  // Lets the child circle inherit visibility
+        ));
+    }
+}
+
+/// Pick a random point on one of the playfield edges
+fn random_edge_position(rng: &mut impl Rng, playfield: Vec2) -> Vec2 {
+    let half = playfield / 2.0;
+    // Choose random edge to spawn from (0=left, 1=right, 2=bottom, 3=top)
+    let edge = rng.random_range(0..4);
+    match edge {
+        0 => Vec2::new(-half.x, rng.random_range(-half.y..half.y)),  // Left edge
+        1 => Vec2::new(half.x, rng.random_range(-half.y..half.y)),   // Right edge
+        2 => Vec2::new(rng.random_range(-half.x..half.x), -half.y),  // Bottom edge
+        _ => Vec2::new(rng.random_range(-half.x..half.x), half.y),   // Top edge
+    }
+}
+
+/// Wave progression: each wave is larger, with a break after one is wiped out
+#[derive(Resource)]
+struct WaveState {
+    wave: u32,             // Current wave, starting at 1 with the initial flock
+    growth: usize,         // Extra boids per wave on top of `BoidSettings::target_count`
+    break_timer: Timer,    // Countdown between a cleared wave and the next
+    in_break: bool,        // Whether the countdown is running
+    to_spawn: usize,       // Boids of the current wave still waiting to enter
+    spawn_timer: Timer,    // Repeating tick that lets the next batch of the wave in
+    spawn_batch: usize,    // Boids entering per spawn tick
+    target_waves: u32,     // Clearing this wave wins the game
+}
+
+impl Default for WaveState {
+    fn default() -> Self {
+        Self {
+            wave: 1,
+            growth: 25,
+            break_timer: Timer::from_seconds(5.0, TimerMode::Once),
+            in_break: false,
+            to_spawn: 0,
+            spawn_timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+            spawn_batch: 5,  // 50 boids per second, whatever the frame rate
+            target_waves: 10,
+        }
+    }
+}
+
+impl WaveState {
+    /// Number of boids in the given wave
+    fn wave_size(&self, wave: u32, first_wave: usize) -> usize {
+        first_wave + (wave.saturating_sub(1) as usize) * self.growth
+    }
+}
+
+/// Endless-run pressure: boids get faster the longer a game lasts
+#[derive(Resource)]
+struct Difficulty {
+    elapsed: f32,            // Seconds of play this game
+    speed_multiplier: f32,   // Applied on top of each boid's own top speed
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            speed_multiplier: 1.0,
+        }
+    }
+}
+
+impl Difficulty {
+    /// Speed multiplier gained per second of play
+    const RAMP_PER_SECOND: f32 = 0.002;
+    
+    /// Cap on the multiplier (reached after about four minutes)
+    const MAX_SPEED_MULTIPLIER: f32 = 1.5;
+}
+
+fn ramp_difficulty(mut difficulty: ResMut<Difficulty>, time: Res<Time>) {
+    difficulty.elapsed += time.delta_secs();
+    difficulty.speed_multiplier =
+        (1.0 + difficulty.elapsed * Difficulty::RAMP_PER_SECOND).min(Difficulty::MAX_SPEED_MULTIPLIER);
+}
+
+/// Closest two boids entering in the same frame may be, unless the edge runs out of room
+const MIN_SPAWN_SPACING: f32 = 30.0;
+
+/// Candidate positions tried for a spawn before settling for the last one
+const MAX_SPAWN_ATTEMPTS: usize = 8;
+
+/// Send boids in waves: once every boid is gone, wait out a break and spawn a bigger wave
+#[allow(clippy::too_many_arguments)]
+fn respawn_boids(
+    mut commands: Commands,
+    mut next_id: ResMut<NextBoidId>,
+    mut waves: ResMut<WaveState>,
+    boids: Query<&Boid>,
+    settings: Res<BoidSettings>,
+    goal: Res<GoalZone>,
+    bounds: Res<WorldBounds>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let playfield = bounds.size;
+    
+    if waves.to_spawn == 0 {
+        // Wave still in progress
+        if !boids.is_empty() {
+            return;
+        }
+        
+        // Surviving the last wave wins instead of starting a break
+        if waves.wave >= waves.target_waves {
+            info!("Wave {} cleared: victory", waves.wave);
+            next_state.set(GameState::Victory);
+            return;
+        }
+        
+        if !waves.in_break {
+            waves.in_break = true;
+            waves.break_timer.reset();
+            info!("Wave {} cleared", waves.wave);
+        }
+        waves.break_timer.tick(time.delta());
+        if !waves.break_timer.finished() {
+            return;
+        }
+        
+        waves.in_break = false;
+        waves.wave += 1;
+        waves.to_spawn = waves.wave_size(waves.wave, settings.target_count);
+        info!("Wave {} incoming: {} boids", waves.wave, waves.to_spawn);
+        commands.trigger(WaveStarted {
+            wave: waves.wave,
+            size: waves.to_spawn,
+        });
+    }
+    
+    let rng = &mut game_rng.rng;
+    
+    let mut spawned_positions: Vec<Vec2> = Vec::new();
+    
+    // Spawn a batch per elapsed spawn tick (the wave streams in from the edges);
+    // a slow frame that spans several ticks catches up with several batches
+    waves.spawn_timer.tick(time.delta());
+    let ticks = waves.spawn_timer.times_finished_this_tick() as usize;
+    let batch = waves.to_spawn.min(ticks * waves.spawn_batch);
+    waves.to_spawn -= batch;
+    for _ in 0..batch {
+        // Pick an edge point that isn't on top of another boid from this frame,
+        // falling back to the last candidate so spawning never stalls.
+        // Boids never enter on the goal edge, which would end the game instantly.
+        let edge_position = |rng: &mut StdRng| loop {
+            let candidate = random_edge_position(rng, playfield);
+            if !goal.contains(candidate, playfield) {
+                break candidate;
+            }
+        };
+        let mut position = edge_position(rng);
+        for _ in 1..MAX_SPAWN_ATTEMPTS {
+            if spawned_positions.iter().all(|other| other.distance(position) >= MIN_SPAWN_SPACING) {
+                break;
+            }
+            position = edge_position(rng);
+        }
+        spawned_positions.push(position);
+        
+        // Random initial velocity
+        let velocity = Vec2::new(
+            rng.random_range(-150.0..150.0),
+            rng.random_range(-150.0..150.0),
+        );
+        
+        // Spawn new boid at edge
+        let kind = BoidKind::random(rng);
+        let id = next_id.next();
+        let entity = commands.spawn((
+            Boid {
+                velocity,
+                acceleration: Vec2::ZERO,
+                health: kind.max_health(),  // Full health
+                max_health: kind.max_health(),
+                max_speed: settings.max_speed * kind.speed_scale(),
+                energy: 1.0,  // Fully rested
+                damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
+                team: rng.random_range(0..2),  // Reinforce either swarm
+                generation: 0,
+            },
+            kind,
+            id,
+            Transform::from_translation(position.extend(0.0)),  // Convert Vec2 to Vec3
+        )).id();
+        commands.trigger(BoidSpawned { entity, id });
+    }
+}
+
+/// Countdown text shown in the top center between waves
+#[derive(Component)]
+struct WaveText;
+
+fn setup_wave_text(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),                      // 40px from top
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                WaveText,
+            ));
+        });
+}
+
+fn update_wave_text(waves: Res<WaveState>, mut text: Query<&mut Text, With<WaveText>>) {
+    let Ok(mut text) = text.single_mut() else { return; };
+    
+    let new_text = if waves.in_break {
+        format!(
+            "Wave {}/{} in {:.0}s",
+            waves.wave + 1,
+            waves.target_waves,
+            waves.break_timer.remaining_secs().ceil(),
+        )
+    } else {
+        String::new()
+    };
+    
+    // Only write on change so the text isn't re-laid out every frame
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+// ===== PREDATOR =====
+
+/// Hunter that chases the nearest boid and eats any it catches
+#[derive(Component)]
+struct Predator {
+    velocity: Vec2,
+    speed: f32,          // Top speed in pixels per second
+    catch_radius: f32,   // Boids this close are eaten
+}
+
+/// Steer predators towards their nearest boid and eat boids in reach
+fn update_predator(
+    mut commands: Commands,
+    mut predators: Query<(&mut Predator, &mut Transform), Without<Boid>>,
+    mut boids: Query<(Entity, &mut Boid, &BoidId, &BoidKind, &Transform)>,
+    bounds: Res<WorldBounds>,
+    time: Res<Time>,
+) {
+    let half = bounds.size / 2.0;
+    let turn_rate = 2.0;  // How quickly the predator bends towards its prey
+    
+    for (mut predator, mut transform) in &mut predators {
+        let pos = transform.translation.truncate();
+        
+        let nearest = boids
+            .iter()
+            .filter(|(_, boid, _, _, _)| boid.health > 0.0)
+            .map(|(_, _, _, _, boid_transform)| boid_transform.translation.truncate())
+            .min_by(|a, b| a.distance(pos).total_cmp(&b.distance(pos)));
+        
+        if let Some(prey) = nearest {
+            let desired = (prey - pos).normalize_or_zero() * predator.speed;
+            let steering = (desired - predator.velocity) * turn_rate * time.delta_secs();
+            predator.velocity = (predator.velocity + steering).clamp_length_max(predator.speed);
+        }
+        
+        let new_pos = (pos + predator.velocity * time.delta_secs()).clamp(-half, half);
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+        if predator.velocity != Vec2::ZERO {
+            let angle = predator.velocity.y.atan2(predator.velocity.x) - std::f32::consts::FRAC_PI_2;
+            transform.rotation = Quat::from_rotation_z(angle);
+        }
+        
+        for (boid_entity, mut boid, boid_id, kind, boid_transform) in &mut boids {
+            // Skip boids a turret already killed this frame (despawn is deferred)
+            if boid.health <= 0.0 || boid_transform.translation.truncate().distance(new_pos) > predator.catch_radius {
+                continue;
+            }
+            boid.health = 0.0;
+            commands.entity(boid_entity).despawn();
+            commands.trigger(BoidDespawned {
+                entity: boid_entity,
+                id: *boid_id,
+                kind: *kind,
+                cause: DespawnCause::Eaten,
+            });
+        }
+    }
+}
+
+// ===== LIFECYCLE OBSERVERS =====
+
+/// Debug log for every boid spawn
+fn log_boid_spawned(trigger: Trigger<BoidSpawned>) {
+    let event = trigger.event();
+    debug!("Boid {:?} spawned as {}", event.id, event.entity);
+}
+
+/// Debug log for every boid despawn and its cause
+fn log_boid_despawned(trigger: Trigger<BoidDespawned>) {
+    let event = trigger.event();
+    debug!("Boid {:?} ({}) despawned: {:?}", event.id, event.entity, event.cause);
+}
+
+// ===== KILL FEED =====
+
+/// Marker for the kill feed container in the bottom right corner
+#[derive(Component)]
+struct KillFeedRoot;
+
+/// A single kill feed line that fades out towards the end of its lifetime
+#[derive(Component)]
+struct KillFeedEntry {
+    lifetime: Timer,
+}
+
+/// Kill feed and wave lines currently on screen, oldest first
+#[derive(Resource, Default)]
+struct KillFeed {
+    entries: VecDeque<Entity>,
+}
+
+/// Create the (initially empty) kill feed container
+fn setup_kill_feed(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),                   // 40px from bottom
+            right: Val::Px(40.0),                    // 40px from right
+            flex_direction: FlexDirection::Column,   // Newest line at the bottom
+            align_items: AlignItems::FlexEnd,        // Right-align lines
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        KillFeedRoot,
+    ));
+}
+
+/// Append a kill feed line whenever a turret kills a boid
+fn push_kill_feed(
+    trigger: Trigger<BoidDespawned>,
+    mut commands: Commands,
+    mut feed: ResMut<KillFeed>,
+    root: Query<Entity, With<KillFeedRoot>>,
+) {
+    let event = trigger.event();
+    let DespawnCause::KilledByTurret(turret_id) = event.cause else { return; };
+    let Ok(root) = root.single() else { return; };
+    
+    let line = format!("Turret #{} killed a {:?} boid", turret_id.0, event.kind);
+    push_feed_line(&mut commands, &mut feed, root, line, Color::WHITE);
+}
+
+/// Announce each new wave in the kill feed
+fn push_wave_feed(
+    trigger: Trigger<WaveStarted>,
+    mut commands: Commands,
+    mut feed: ResMut<KillFeed>,
+    root: Query<Entity, With<KillFeedRoot>>,
+) {
+    let event = trigger.event();
+    let Ok(root) = root.single() else { return; };
+    
+    let line = format!("Wave {} started: {} boids", event.wave, event.size);
+    push_feed_line(&mut commands, &mut feed, root, line, Color::srgb(1.0, 0.85, 0.3));  // Gold stands out from kills
+}
+
+/// Add a line to the bottom of the feed, dropping the oldest past the limit
+fn push_feed_line(commands: &mut Commands, feed: &mut KillFeed, root: Entity, line: String, color: Color) {
+    let max_entries = 6;  // Oldest lines are dropped beyond this
+    
+    let entry = commands
+        .spawn((
+            Text::new(line),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(color),
+            KillFeedEntry {
+                lifetime: Timer::from_seconds(5.0, TimerMode::Once),
+            },
+        ))
+        .id();
+    commands.entity(root).add_child(entry);
+    feed.entries.push_back(entry);
+    
+    // Cap the feed length by dropping the oldest lines
+    while feed.entries.len() > max_entries {
+        if let Some(oldest) = feed.entries.pop_front() {
+            commands.entity(oldest).despawn();
+        }
+    }
+}
+
+/// Fade kill feed lines out over their final second and remove expired ones
+fn update_kill_feed(
+    mut commands: Commands,
+    mut feed: ResMut<KillFeed>,
+    mut entries: Query<(&mut KillFeedEntry, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    let fade_duration = 1.0;  // Seconds spent fading out
+    
+    feed.entries.retain(|&entity| {
+        let Ok((mut entry, mut color)) = entries.get_mut(entity) else { return false; };
+        entry.lifetime.tick(time.delta());
+        
+        if entry.lifetime.finished() {
+            commands.entity(entity).despawn();
+            return false;
+        }
+        
+        let alpha = (entry.lifetime.remaining_secs() / fade_duration).min(1.0);
+        color.0 = color.0.with_alpha(alpha);
+        true
+    });
+}
+
+// ===== PROTECTED ZONE =====
+
+/// Circular region the turrets must defend; boids reaching it count as leaks
+#[derive(Resource)]
+struct ProtectZone {
+    center: Vec2,     // World-space center of the zone
+    radius: f32,      // Zone radius in pixels
+    leaks: u32,       // Boids that have reached the zone so far
+    max_leaks: u32,   // Leaks allowed before the defense is lost
+}
+
+impl Default for ProtectZone {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            radius: 60.0,
+            leaks: 0,
+            max_leaks: 20,
+        }
+    }
+}
+
+impl ProtectZone {
+    /// Whether a world position lies inside the zone
+    fn contains(&self, position: Vec2) -> bool {
+        position.distance(self.center) < self.radius
+    }
+    
+    /// Whether enough boids have leaked to lose the defense
+    fn is_overrun(&self) -> bool {
+        self.leaks >= self.max_leaks
+    }
+}
+
+/// Draw the protected zone as a translucent circle behind everything else
+fn setup_protect_zone(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    zone: Res<ProtectZone>,
+) {
+    commands.spawn((
+        Mesh2d(visual_assets.unit_circle_mesh.clone()),
+        MeshMaterial2d(visual_assets.zone_material.clone()),
+        Transform::from_translation(zone.center.extend(-2.0))  // Behind turrets and boids
+            .with_scale(Vec3::splat(zone.radius)),
+    ));
+}
+
+/// Remove boids that reach the protected zone and count them as leaks
+fn detect_zone_leaks(
+    mut commands: Commands,
+    mut zone: ResMut<ProtectZone>,
+    mut boids: Query<(Entity, &mut Boid, &BoidId, &BoidKind, &Transform)>,
+) {
+    for (entity, mut boid, id, kind, transform) in &mut boids {
+        // Skip boids already killed this frame (despawn is deferred)
+        if boid.health <= 0.0 || !zone.contains(transform.translation.truncate()) {
+            continue;
+        }
+        
+        boid.health = 0.0;  // Mark dead so turrets don't also claim it this frame
+        commands.entity(entity).despawn();
+        commands.trigger(BoidDespawned {
+            entity,
+            id: *id,
+            kind: *kind,
+            cause: DespawnCause::ReachedZone,
+        });
+        
+        let was_overrun = zone.is_overrun();
+        zone.leaks += 1;
+        if !was_overrun && zone.is_overrun() {
+            warn!("Protected zone overrun: {} boids leaked", zone.leaks);
+        }
+    }
+}
+
+// ===== TURRET CURIOSITY =====
+
+/// Transient attraction of nearby boids towards newly placed turrets
+///
+/// Disabled by default; when enabled, each `TurretPlaced` event adds a source
+/// whose pull decays linearly to zero over `duration` seconds.
+#[derive(Resource)]
+struct Curiosity {
+    enabled: bool,                  // Whether new turrets attract boids at all
+    radius: f32,                    // Boids within this distance feel the pull
+    strength: f32,                  // Peak attraction force
+    duration: f32,                  // Seconds for the attraction to fade out
+    sources: Vec<CuriositySource>,  // Turrets boids are still curious about
+}
+
+/// A turret boids are currently curious about
+struct CuriositySource {
+    position: Vec2,
+    timer: Timer,
+}
+
+impl Default for Curiosity {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 400.0,
+            strength: 300.0,
+            duration: 3.0,
+            sources: Vec::new(),
+        }
+    }
+}
+
+/// Start a curiosity source when a turret is placed
+fn spark_curiosity(trigger: Trigger<TurretPlaced>, mut curiosity: ResMut<Curiosity>) {
+    if !curiosity.enabled {
+        return;
+    }
+    
+    let timer = Timer::from_seconds(curiosity.duration, TimerMode::Once);
+    curiosity.sources.push(CuriositySource {
+        position: trigger.event().position,
+        timer,
+    });
+}
+
+/// Tick curiosity sources and drop the ones that have faded out
+fn decay_curiosity(mut curiosity: ResMut<Curiosity>, time: Res<Time>) {
+    curiosity.sources.retain_mut(|source| {
+        source.timer.tick(time.delta());
+        !source.timer.finished()
+    });
+}
+
+// ===== FLOCK FORMATIONS =====
+
+/// Formation points assigned to boids, steering them into a shape
+///
+/// Press 1 for a heart, 2 for a ring, 3 for the dark pixels of
+/// `assets/formation.png`, and 0 to release the flock back to flocking.
+#[derive(Resource, Default)]
+struct Formation {
+    assignments: HashMap<Entity, Vec2>,   // Target world position per boid
+    pending_image: Option<Handle<Image>>, // Image shape waiting to finish loading
+}
+
+/// Built-in formation shapes
+#[derive(Clone, Copy, Debug)]
+enum FormationShape {
+    Heart,
+    Ring,
+}
+
+impl FormationShape {
+    /// Whether a point in normalized shape space (roughly -1.5..1.5) lies inside the shape
+    fn contains(self, point: Vec2) -> bool {
+        match self {
+            // Classic implicit heart curve: (x² + y² - 1)³ - x²y³ <= 0
+            FormationShape::Heart => {
+                let a = point.x * point.x + point.y * point.y - 1.0;
+                a * a * a - point.x * point.x * point.y * point.y * point.y <= 0.0
+            }
+            FormationShape::Ring => (0.8..=1.2).contains(&point.length()),
+        }
+    }
+}
+
+/// Pick `count` points spread evenly over a set of candidate points
+fn spread_points(candidates: &[Vec2], count: usize) -> Vec<Vec2> {
+    if candidates.len() <= count {
+        return candidates.to_vec();
+    }
+    
+    let stride = candidates.len() as f32 / count as f32;
+    (0..count)
+        .map(|i| candidates[(i as f32 * stride) as usize])
+        .collect()
+}
+
+/// Sample up to `count` world points filling a built-in shape of the given world size
+fn sample_shape_points(shape: FormationShape, size: f32, count: usize) -> Vec<Vec2> {
+    let resolution = 80;  // Grid cells per side when scanning the shape
+    let extent = 1.5;     // Half-size of the normalized shape space
+    
+    let mut candidates = Vec::new();
+    for row in 0..resolution {
+        for column in 0..resolution {
+            let point = Vec2::new(
+                (column as f32 / (resolution - 1) as f32 * 2.0 - 1.0) * extent,
+                (row as f32 / (resolution - 1) as f32 * 2.0 - 1.0) * extent,
+            );
+            if shape.contains(point) {
+                candidates.push(point / extent * size / 2.0);
+            }
+        }
+    }
+    spread_points(&candidates, count)
+}
+
+/// Sample up to `count` world points from the dark pixels of an image, fitted to `size`
+fn sample_image_points(image: &Image, size: f32, count: usize) -> Vec<Vec2> {
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let scale = size / width.max(height) as f32;
+    
+    let mut candidates = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let Ok(color) = image.get_color_at(x, y) else { continue; };
+            let srgba = color.to_srgba();
+            let luminance = 0.299 * srgba.red + 0.587 * srgba.green + 0.114 * srgba.blue;
+            if srgba.alpha > 0.5 && luminance < 0.5 {
+                // Center the image on the origin and flip Y (image rows go down)
+                candidates.push(Vec2::new(
+                    (x as f32 - width as f32 / 2.0) * scale,
+                    (height as f32 / 2.0 - y as f32) * scale,
+                ));
+            }
+        }
+    }
+    spread_points(&candidates, count)
+}
+
+/// Greedily assign each formation point to the nearest unassigned boid
+fn assign_formation(points: &[Vec2], boids: &[(Entity, Vec2)]) -> HashMap<Entity, Vec2> {
+    let mut free: Vec<(Entity, Vec2)> = boids.to_vec();
+    let mut assignments = HashMap::new();
+    
+    for &point in points {
+        let Some(index) = free
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.1.distance(point).total_cmp(&b.1.distance(point)))
+            .map(|(index, _)| index)
+        else {
+            break;  // More points than boids
+        };
+        let (entity, _) = free.swap_remove(index);
+        assignments.insert(entity, point);
+    }
+    assignments
+}
+
+/// Handle the formation hotkeys
+fn formation_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut formation: ResMut<Formation>,
+    boids: Query<(Entity, &Transform), With<Boid>>,
+) {
+    let formation_size = 600.0;  // World-space size of the formed shape
+    
+    let shape = if keyboard.just_pressed(KeyCode::Digit1) {
+        Some(FormationShape::Heart)
+    } else if keyboard.just_pressed(KeyCode::Digit2) {
+        Some(FormationShape::Ring)
+    } else {
+        None
+    };
+    
+    if let Some(shape) = shape {
+        let boid_positions: Vec<(Entity, Vec2)> = boids
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation.truncate()))
+            .collect();
+        let points = sample_shape_points(shape, formation_size, boid_positions.len());
+        formation.assignments = assign_formation(&points, &boid_positions);
+        formation.pending_image = None;
+    } else if keyboard.just_pressed(KeyCode::Digit3) {
+        // Sampled once the image has loaded (see `assign_image_formation`)
+        formation.pending_image = Some(asset_server.load("formation.png"));
+    } else if keyboard.just_pressed(KeyCode::Digit0) {
+        formation.assignments.clear();
+        formation.pending_image = None;
+    }
+}
+
+/// Assign boids to the image formation once its image has finished loading
+fn assign_image_formation(
+    images: Res<Assets<Image>>,
+    mut formation: ResMut<Formation>,
+    boids: Query<(Entity, &Transform), With<Boid>>,
+) {
+    let formation_size = 600.0;  // World-space size of the formed shape
+    
+    let Some(handle) = &formation.pending_image else { return; };
+    let Some(image) = images.get(handle) else { return; };
+    
+    let boid_positions: Vec<(Entity, Vec2)> = boids
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
+    let points = sample_image_points(image, formation_size, boid_positions.len());
+    formation.assignments = assign_formation(&points, &boid_positions);
+    formation.pending_image = None;
+}
+
+// ===== LEADER PATH =====
+
+/// Scripted anchor that loops along a route of waypoints for leader boids to chase
+///
+/// Disabled by default; press L to start it. Every `leader_every`-th boid (by id)
+/// is a leader, and the rest of the flock follows them through flocking.
+#[derive(Resource)]
+struct LeaderPath {
+    enabled: bool,
+    waypoints: Vec<Vec2>,   // Route the anchor loops around
+    speed: f32,             // Anchor travel speed in pixels per second
+    leader_every: u32,      // One boid in this many leads
+    pull: f32,              // How hard leaders steer towards the anchor
+    anchor: Vec2,           // Current anchor position
+    next_waypoint: usize,   // Index of the waypoint the anchor is heading to
+}
+
+impl Default for LeaderPath {
+    fn default() -> Self {
+        let waypoints = vec![
+            Vec2::new(-600.0, 300.0),
+            Vec2::new(600.0, 300.0),
+            Vec2::new(600.0, -300.0),
+            Vec2::new(-600.0, -300.0),
+        ];
+        Self {
+            enabled: false,
+            anchor: waypoints[0],
+            waypoints,
+            speed: 150.0,
+            leader_every: 10,
+            pull: 2.0,
+            next_waypoint: 1,
+        }
+    }
+}
+
+impl LeaderPath {
+    fn is_leader(&self, id: BoidId) -> bool {
+        id.0.is_multiple_of(self.leader_every)
+    }
+    
+    /// Move the anchor `distance` along the route, passing through as many waypoints as needed
+    fn advance(&mut self, mut distance: f32) {
+        if self.waypoints.len() < 2 {
+            return;  // Nothing to travel between
+        }
+        
+        while distance > 0.0 {
+            let target = self.waypoints[self.next_waypoint];
+            let remaining = self.anchor.distance(target);
+            if remaining > distance {
+                self.anchor += (target - self.anchor) / remaining * distance;
+                return;
+            }
+            
+            // Reached the waypoint: carry the leftover distance into the next leg
+            self.anchor = target;
+            distance -= remaining;
+            self.next_waypoint = (self.next_waypoint + 1) % self.waypoints.len();
+        }
+    }
+}
+
+fn advance_leader_path(mut leader_path: ResMut<LeaderPath>, time: Res<Time>) {
+    if leader_path.enabled {
+        let distance = leader_path.speed * time.delta_secs();
+        leader_path.advance(distance);
+    }
+}
+
+fn toggle_leader_path(keyboard: Res<ButtonInput<KeyCode>>, mut leader_path: ResMut<LeaderPath>) {
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        leader_path.enabled = !leader_path.enabled;
+        info!("Leader path {}", if leader_path.enabled { "started" } else { "stopped" });
+    }
+}
+
+fn draw_leader_path(leader_path: Res<LeaderPath>, mut gizmos: Gizmos) {
+    if !leader_path.enabled {
+        return;
+    }
+    
+    let route_color = Color::srgba(1.0, 0.8, 0.3, 0.3);
+    for (index, &waypoint) in leader_path.waypoints.iter().enumerate() {
+        let next = leader_path.waypoints[(index + 1) % leader_path.waypoints.len()];
+        gizmos.line_2d(waypoint, next, route_color);
+    }
+    gizmos.circle_2d(leader_path.anchor, 10.0, Color::srgb(1.0, 0.8, 0.3));
+}
+
+// ===== FOLLOW THE LEADER =====
+
+/// Scripted boid that slowly circles a point, ignoring the flock
+///
+/// Boids within `BoidSettings::leader_follow_radius` steer after it, so the flock
+/// can be led around. It isn't a `Boid`, so turrets and the predator leave it alone.
+#[derive(Component)]
+struct Leader {
+    center: Vec2,   // Middle of the circle it travels
+    radius: f32,
+    speed: f32,     // Travel speed along the circle in pixels per second
+    angle: f32,     // Current position around the circle in radians
+}
+
+impl Default for Leader {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            radius: 350.0,
+            speed: 90.0,  // Well below boid top speed so followers can keep up
+            angle: 0.0,
+        }
+    }
+}
+
+impl Leader {
+    fn position(&self) -> Vec2 {
+        self.center + Vec2::from_angle(self.angle) * self.radius
+    }
+}
+
+/// Move the leader counter-clockwise around its circle, facing along it
+fn move_leader(mut leaders: Query<(&mut Leader, &mut Transform)>, time: Res<Time>) {
+    for (mut leader, mut transform) in &mut leaders {
+        leader.angle = (leader.angle + leader.speed / leader.radius * time.delta_secs()) % std::f32::consts::TAU;
+        let position = leader.position();
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        // The tangent is a quarter turn ahead of the angle and the mesh points up,
+        // so the two quarter turns cancel out
+        transform.rotation = Quat::from_rotation_z(leader.angle);
+    }
+}
+
+// ===== GRAVITY WELLS =====
+
+/// Point mass that bends boid paths with an inverse-square pull
+#[derive(Component)]
+struct GravityWell {
+    strength: f32,      // Pull at distance d is strength / d², capped at the boid max force
+    core_radius: f32,   // Distances below this count as this, avoiding the singularity
+}
+
+/// Right-click empty space to place a gravity well, or right-click a well to remove it
+fn place_gravity_well(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    wells: Query<(Entity, &GravityWell, &Transform)>,
+) {
+    // Ctrl + right-click repels the flock instead (see `update_cursor_force`)
+    if !mouse.just_pressed(MouseButton::Right) || keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let Ok(window) = window_query.single() else { return; };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return; };
+    let Some(cursor) = cursor_world_position(window, camera, camera_transform) else { return; };
+    
+    // Clicking inside an existing well's core removes it
+    if let Some((entity, _, _)) = wells
+        .iter()
+        .find(|(_, well, transform)| transform.translation.truncate().distance(cursor) < well.core_radius)
+    {
+        commands.entity(entity).despawn();
+        return;
+    }
+    
+    let well = GravityWell {
+        strength: 4_000_000.0,  // About max force at 100px, a gentle tug at 300px
+        core_radius: 30.0,
+    };
+    commands.spawn((
+        Mesh2d(visual_assets.unit_circle_mesh.clone()),
+        MeshMaterial2d(visual_assets.gravity_well_material.clone()),
+        Transform::from_translation(cursor.extend(-2.0)).with_scale(Vec3::splat(well.core_radius)),
+        well,
+    ));
+}
+
+// ===== CURSOR FORCE =====
+
+/// Attraction or repulsion around the cursor while Ctrl and a mouse button are held
+///
+/// Ctrl + left pulls boids in, Ctrl + right pushes them away. Written by
+/// `update_cursor_force`, applied in `update_boids`.
+#[derive(Resource)]
+struct CursorForce {
+    point: Option<Vec2>,  // Cursor world position while active
+    repel: bool,          // Push away instead of pulling in
+    radius: f32,          // Boids further than this are unaffected
+    strength: f32,        // Force at the cursor, fading linearly to zero at the radius
+}
+
+impl Default for CursorForce {
+    fn default() -> Self {
+        Self {
+            point: None,
+            repel: false,
+            radius: 300.0,
+            strength: 800.0,
+        }
+    }
+}
+
+/// Track the cursor and which button is held for the cursor force
+fn update_cursor_force(
+    mut cursor_force: ResMut<CursorForce>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    cursor_force.point = None;
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let attract = mouse.pressed(MouseButton::Left);
+    let repel = mouse.pressed(MouseButton::Right);
+    if attract == repel {
+        return;  // Neither, or both cancelling out
+    }
+    let Ok(window) = window_query.single() else { return; };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return; };
+    cursor_force.point = cursor_world_position(window, camera, camera_transform);
+    cursor_force.repel = repel;
+}
+
+// ===== PANIC DISPERSAL =====
+
+/// Countdown for a panic that scatters the flock outward from its center
+///
+/// Started with P and applied in `update_boids`. The push fades out over the
+/// duration, so boids drift back into flocking instead of snapping back.
+#[derive(Resource)]
+struct PanicTimer {
+    timer: Timer,
+    strength: f32,  // Outward push when the panic starts
+}
+
+impl Default for PanicTimer {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(1.5, TimerMode::Once);
+        timer.tick(timer.duration());  // Not panicking until P is pressed
+        Self {
+            timer,
+            strength: 1200.0,  // Well above the flocking forces
+        }
+    }
+}
+
+impl PanicTimer {
+    /// Outward push right now, zero once the panic is over
+    fn current_strength(&self) -> f32 {
+        self.strength * self.timer.fraction_remaining()
+    }
+}
+
+/// Start (or restart) a panic on P
+fn trigger_panic(keyboard: Res<ButtonInput<KeyCode>>, mut panic: ResMut<PanicTimer>) {
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        panic.timer.reset();
+        info!("Panic!");
+    }
+}
+
+fn tick_panic(mut panic: ResMut<PanicTimer>, time: Res<Time>) {
+    panic.timer.tick(time.delta());
+}
+
+// ===== TURRET STATISTICS =====
+
+/// Log every turret's lifetime stats and the MVP when the app is closing
+fn log_turret_summary(
+    mut exit_events: EventReader<AppExit>,
+    turrets: Query<(&TurretId, &TurretStats)>,
+) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    
+    let mut ranked: Vec<(&TurretId, &TurretStats)> = turrets.iter().collect();
+    ranked.sort_by_key(|(id, _)| id.0);
+    for (id, stats) in &ranked {
+        info!("Turret #{}: {}", id.0, stats.describe());
+    }
+    
+    if let Some((id, stats)) = mvp_turret(ranked) {
+        info!("MVP turret: #{} with {} kills", id.0, stats.kills);
+    }
+}
+
+// ===== DAMAGE TYPES =====
+
+/// Kind of damage a weapon deals, looked up against boid resistances
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum DamageType {
+    Laser,    // Continuous turret beams
+    Kinetic,  // Homing turret rounds
+    Splash,   // Artillery blasts
+}
+
+/// Damage multipliers per (damage type, boid kind); anything missing takes full damage
+///
+/// Values below 1.0 are resistances, above 1.0 weaknesses.
+#[derive(Resource, Default)]
+struct Resistances {
+    table: HashMap<(DamageType, BoidKind), f32>,
+}
+
+impl Resistances {
+    fn multiplier(&self, damage_type: DamageType, kind: BoidKind) -> f32 {
+        self.table.get(&(damage_type, kind)).copied().unwrap_or(1.0)
+    }
+}
+
+// ===== AUTO-ARTILLERY =====
+
+/// Emplacement that holds fire until boids bunch up, then shells the cluster
+///
+/// Measures density as the number of boids within `density_radius` of each
+/// candidate aim point (a boid in range) and fires once the densest point
+/// reaches `density_threshold`, damaging everything within `splash_radius`.
+#[derive(Component)]
+struct Artillery {
+    range: f32,               // Maximum distance to an aim point
+    density_radius: f32,      // Radius used to count boids around an aim point
+    density_threshold: usize, // Boids needed around the aim point before firing
+    splash_radius: f32,       // Radius of the blast
+    splash_damage: f32,       // Health removed from each boid in the blast
+    reload_timer: Timer,      // Delay between shells
+}
+
+/// Short-lived circle marking where an artillery shell landed
+#[derive(Component)]
+struct Blast {
+    lifetime: Timer,
+}
+
+/// Fire artillery at the densest boid cluster in range once it is dense enough
+fn fire_artillery(
+    mut commands: Commands,
+    mut artillery: Query<(&mut Artillery, &TurretId, &mut TurretStats, &Transform)>,
+    mut boids: Query<(Entity, &mut Boid, &BoidId, &BoidKind, &Transform)>,
+    resistances: Res<Resistances>,
+    mut score: ResMut<Score>,
+    mut next_id: ResMut<NextBoidId>,
+    time: Res<Time>,
+) {
+    for (mut artillery, turret_id, mut stats, artillery_transform) in &mut artillery {
+        artillery.reload_timer.tick(time.delta());
+        if !artillery.reload_timer.finished() {
+            continue;
+        }
+        
+        let origin = artillery_transform.translation.truncate();
+        let positions: Vec<Vec2> = boids
+            .iter()
+            .filter(|(_, boid, _, _, _)| boid.health > 0.0)
+            .map(|(_, _, _, _, transform)| transform.translation.truncate())
+            .collect();
+        
+        // Find the in-range boid with the most neighbors around it
+        let densest = positions
+            .iter()
+            .filter(|pos| pos.distance(origin) < artillery.range)
+            .map(|&pos| {
+                let density = positions
+                    .iter()
+                    .filter(|other| other.distance(pos) < artillery.density_radius)
+                    .count();
+                (pos, density)
+            })
+            .max_by_key(|&(_, density)| density);
+        
+        let Some((aim_point, density)) = densest else { continue; };
+        if density < artillery.density_threshold {
+            continue;  // Hold fire until the flock clumps up
+        }
+        
+        // Splash damage everything caught in the blast
+        for (boid_entity, mut boid, boid_id, kind, boid_transform) in &mut boids {
+            if boid.health <= 0.0
+                || boid_transform.translation.truncate().distance(aim_point) > artillery.splash_radius
+            {
+                continue;
+            }
+            
+            let multiplier = resistances.multiplier(DamageType::Splash, *kind);
+            let damage = (artillery.splash_damage * multiplier).min(boid.health);
+            boid.health -= damage;
+            stats.damage_dealt += damage;
+            boid.damage_flash_timer = Timer::from_seconds(0.5, TimerMode::Once);
+            
+            if boid.health <= 0.0 {
+                let victim = (boid_entity, &*boid, boid_id, kind, boid_transform);
+                kill_boid(&mut commands, &mut score, &mut next_id, Some(&mut stats), *turret_id, victim);
+            }
+        }
+        
+        stats.shots_fired += 1;
+        artillery.reload_timer.reset();
+        
+        commands.trigger(ArtilleryImpact {
+            position: aim_point,
+            radius: artillery.splash_radius,
+        });
+    }
+}
+
+/// Mark an artillery impact with a short-lived blast circle
+fn spawn_blast(
+    trigger: Trigger<ArtilleryImpact>,
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+) {
+    let impact = trigger.event();
+    
+    commands.spawn((
+        Mesh2d(visual_assets.unit_circle_mesh.clone()),
+        MeshMaterial2d(visual_assets.blast_materials.last().unwrap().clone()),  // Full-strength flash
+        Transform::from_translation(impact.position.extend(0.5))  // Above boids
+            .with_scale(Vec3::splat(impact.radius)),
+        Blast {
+            lifetime: Timer::from_seconds(0.3, TimerMode::Once),
+        },
+    ));
+}
+
+/// Fade out artillery blast markers and despawn them when done
+fn update_blasts(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    mut blasts: Query<(Entity, &mut Blast, &mut MeshMaterial2d<ColorMaterial>)>,
+    time: Res<Time>,
+) {
+    let ramp = &visual_assets.blast_materials;
+    for (entity, mut blast, mut material) in &mut blasts {
+        blast.lifetime.tick(time.delta());
+        
+        if blast.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Step down the shared fade ramp rather than editing a material per blast
+        let shade = (blast.lifetime.fraction_remaining() * ramp.len() as f32).ceil() as usize;
+        let wanted = &ramp[shade.clamp(1, ramp.len()) - 1];
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
+
+// ===== DEATH PARTICLES =====
+
+/// Short-lived square flung out of a destroyed boid
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+/// Burst a handful of particles in the boid's color where it was shot down
+fn spawn_death_burst(
+    trigger: Trigger<BoidKilled>,
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let kill = trigger.event();
+    let mut rng = rand::rng();
+    
+    let count = 6;
+    let size = 3.0;  // Side length of each square
+    
+    // One material per burst: its particles all fade together
+    let material = materials.add(ColorMaterial::from(kill.color));
+    for i in 0..count {
+        // Evenly spread directions with a little jitter, at varied speeds
+        let angle = i as f32 / count as f32 * std::f32::consts::TAU + rng.random_range(-0.3..0.3);
+        let speed = rng.random_range(60.0..140.0);
+        commands.spawn((
+            Mesh2d(visual_assets.health_bar_mesh.clone()),  // Unit square
+            MeshMaterial2d(material.clone()),
+            Transform::from_translation(kill.position.extend(0.4)).with_scale(Vec3::splat(size)),
+            Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                lifetime: Timer::from_seconds(0.5, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+/// Move particles outward, fade them out and despawn them when done
+fn update_particles(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &MeshMaterial2d<ColorMaterial>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut transform, material_handle) in &mut particles {
+        particle.lifetime.tick(time.delta());
+        
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        
+        transform.translation += (particle.velocity * time.delta_secs()).extend(0.0);
+        // Shared by the whole burst, so every particle writes the same alpha
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            material.color.set_alpha(particle.lifetime.fraction_remaining());
+        }
+    }
+}
+
+// ===== BOID TRAILS =====
+
+/// Faint dot left behind by a moving boid
+#[derive(Component)]
+struct Trail {
+    lifetime: Timer,
+}
+
+/// Trail dot spawning and the cap on how many exist at once
+#[derive(Resource)]
+struct Trails {
+    spawn_timer: Timer,       // Every boid drops a dot each time this fires
+    lifetime: f32,            // Seconds a dot takes to fade out
+    max_dots: usize,          // Oldest dots are removed early past this many
+    dots: VecDeque<Entity>,   // Live dots, oldest first
+}
+
+impl Default for Trails {
+    fn default() -> Self {
+        Self {
+            spawn_timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+            lifetime: 1.0,
+            max_dots: 1500,  // Full trails for 150 boids; bigger waves get shorter ones
+            dots: VecDeque::new(),
+        }
+    }
+}
+
+/// Drop new trail dots, fade the existing ones and remove the expired or excess ones
+fn update_trails(
+    mut commands: Commands,
+    mut trails: ResMut<Trails>,
+    boids: Query<&Transform, With<Boid>>,
+    mut dots: Query<(&mut Trail, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let alpha = 0.3;  // Opacity of a fresh dot
+    
+    for (mut trail, mut sprite) in &mut dots {
+        trail.lifetime.tick(time.delta());
+        sprite.color.set_alpha(alpha * trail.lifetime.fraction_remaining());
+    }
+    
+    // Every dot lives equally long, so the expired ones are always at the front
+    while let Some(&oldest) = trails.dots.front() {
+        if dots.get(oldest).is_ok_and(|(trail, _)| !trail.lifetime.finished()) {
+            break;
+        }
+        commands.entity(oldest).try_despawn();
+        trails.dots.pop_front();
+    }
+    
+    trails.spawn_timer.tick(time.delta());
+    if !trails.spawn_timer.just_finished() {
+        return;
+    }
+    
+    for transform in &boids {
+        let dot = commands.spawn((
+            Sprite::from_color(Color::srgba(1.0, 1.0, 1.0, alpha), Vec2::splat(2.0)),
+            Transform::from_translation(transform.translation.truncate().extend(-0.1)),  // Under the boids
+            Trail {
+                lifetime: Timer::from_seconds(trails.lifetime, TimerMode::Once),
+            },
+        )).id();
+        trails.dots.push_back(dot);
+    }
+    
+    // Over the cap: reuse the budget of the oldest dots
+    while trails.dots.len() > trails.max_dots {
+        if let Some(oldest) = trails.dots.pop_front() {
+            commands.entity(oldest).try_despawn();
+        }
+    }
+}
+
+// ===== AUDIO =====
+
+/// Sound effects, loaded at startup
+#[derive(Resource)]
+struct SoundAssets {
+    laser: Handle<AudioSource>,  // Short falling "pew"
+    death: Handle<AudioSource>,  // Noisy pop when a boid is destroyed
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            laser: asset_server.load("sounds/laser.wav"),
+            death: asset_server.load("sounds/pop.wav"),
+        }
+    }
+}
+
+/// Player-facing audio preferences
+#[derive(Resource)]
+struct AudioSettings {
+    master_volume: f32,    // Scales every sound effect, 0 to 1
+    laser_interval: f32,   // Minimum seconds between laser sounds, however many turrets fire
+    max_death_sounds: usize,  // Death pops allowed to play at once
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 0.5,
+            laser_interval: 0.05,  // At most 20 per second
+            max_death_sounds: 4,
+        }
+    }
+}
+
+/// When each rate-limited sound last started (real elapsed seconds)
+#[derive(Resource)]
+struct SoundThrottle {
+    last_laser: f32,
+}
+
+impl Default for SoundThrottle {
+    fn default() -> Self {
+        Self { last_laser: f32::NEG_INFINITY }
+    }
+}
+
+/// Play a laser sound for a turret shot, skipping it if one started very recently
+fn play_laser_sound(
+    _trigger: Trigger<TurretFired>,
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    audio: Res<AudioSettings>,
+    mut throttle: ResMut<SoundThrottle>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs();
+    if audio.master_volume <= 0.0 || now - throttle.last_laser < audio.laser_interval {
+        return;
+    }
+    throttle.last_laser = now;
+    
+    commands.spawn((
+        AudioPlayer::new(sounds.laser.clone()),
+        PlaybackSettings::DESPAWN.with_volume(bevy::audio::Volume::Linear(audio.master_volume)),
+    ));
+}
+
+/// Marker for death sounds still playing, so a wave wipe doesn't stack dozens of them
+#[derive(Component)]
+struct DeathSound;
+
+/// Play a pop when a turret destroys a boid, unless too many are already playing
+fn play_death_sound(
+    _trigger: Trigger<BoidKilled>,
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    audio: Res<AudioSettings>,
+    playing: Query<(), With<DeathSound>>,
+) {
+    if audio.master_volume <= 0.0 || playing.iter().count() >= audio.max_death_sounds {
+        return;
+    }
+    
+    commands.spawn((
+        DeathSound,
+        AudioPlayer::new(sounds.death.clone()),
+        PlaybackSettings::DESPAWN.with_volume(bevy::audio::Volume::Linear(audio.master_volume)),
+    ));
+}
+
+// ===== TURRET SELECTION =====
+
+/// Marker for turrets picked with the mouse
+#[derive(Component)]
+struct Selected;
+
+/// In-progress selection drag, with the start point in world coordinates
+#[derive(Resource, Default)]
+struct SelectionDrag {
+    start: Option<Vec2>,
+}
+
+/// UI text summarizing the current selection in the top left corner
+#[derive(Component)]
+struct SelectionSummary;
+
+fn setup_selection_summary(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(135.0),                     // Below the time scale readout
+            left: Val::Px(40.0),                     // 40px from left
+            ..default()
+        },
+        SelectionSummary,
+    ));
+}
+
+/// Convert the cursor position to world coordinates, if the cursor is over the window
+fn cursor_world_position(window: &Window, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    camera.viewport_to_world_2d(camera_transform, cursor).ok()
+}
+
+/// Select turrets by clicking one or dragging a box around several (hold Shift to add)
+#[allow(clippy::too_many_arguments)]
+fn select_turrets(
+    mut commands: Commands,
+    mut drag: ResMut<SelectionDrag>,
+    mut gizmos: Gizmos,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    turrets: Query<(Entity, &Transform, Has<Selected>), With<Turret>>,
+) {
+    let Ok(window) = window_query.single() else { return; };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return; };
+    let cursor = cursor_world_position(window, camera, camera_transform);
+    
+    // Ctrl-clicks steer the flock instead of starting a selection
+    if mouse.just_pressed(MouseButton::Left) && !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        drag.start = cursor;
+    }
+    
+    if let (Some(start), Some(end)) = (drag.start, cursor) {
+        let rect = Rect::from_corners(start, end);
+        
+        if mouse.pressed(MouseButton::Left) {
+            gizmos.rect_2d(rect.center(), rect.size(), Color::srgba(0.6, 1.0, 0.6, 0.8));
+        }
+        
+        if mouse.just_released(MouseButton::Left) {
+            let additive = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            
+            for (entity, transform, is_selected) in &turrets {
+                let hit = selection_hit(start, end, transform.translation.truncate());
+                if hit && !is_selected {
+                    commands.entity(entity).try_insert(Selected);
+                } else if !hit && is_selected && !additive {
+                    commands.entity(entity).try_remove::<Selected>();
+                }
+            }
+        }
+    }
+    
+    if mouse.just_released(MouseButton::Left) {
+        drag.start = None;
+    }
+    
+    // Highlight the current selection
+    for (_, transform, is_selected) in &turrets {
+        if is_selected {
+            gizmos.circle_2d(transform.translation.truncate(), 18.0, Color::srgb(0.6, 1.0, 0.6));
+        }
+    }
+}
+
+/// Whether a selection dragged from `start` to `end` picks the turret at `pos`
+///
+/// A box only a few pixels across counts as a click, which picks turrets near `end`.
+fn selection_hit(start: Vec2, end: Vec2, pos: Vec2) -> bool {
+    let click_radius = 15.0;   // How close a plain click must be to a turret
+    let drag_threshold = 5.0;  // Smaller boxes count as a click
+    
+    let rect = Rect::from_corners(start, end);
+    if rect.size().max_element() < drag_threshold {
+        pos.distance(end) < click_radius
+    } else {
+        rect.contains(pos)
+    }
+}
+
+/// Build the kind picked in the build menu where the player clicks empty ground in build mode
+///
+/// Only plain clicks count (drags are box selections), and the spot must be clear of
+/// existing turrets and the artillery emplacement.
+#[allow(clippy::too_many_arguments)]
+fn place_turret(
+    mut commands: Commands,
+    build: Res<BuildMode>,
+    drag: Res<SelectionDrag>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    buttons: Query<&Interaction, With<Button>>,
+    emplacements: Query<(&Transform, &TurretId)>,
+    obstacles: Query<(&Obstacle, &Transform)>,
+    (bounds, zone): (Res<WorldBounds>, Res<ProtectZone>),
+    mut currency: ResMut<Currency>,
+    mut tutorial: ResMut<Tutorial>,
+    (limits, tech): (Res<BuildLimits>, Res<TechProgress>),
+) {
+    if !build.active || !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Ok(window) = window_query.single() else { return; };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return; };
+    let (Some(start), Some(cursor)) = (drag.start, cursor_world_position(window, camera, camera_transform)) else { return; };
+    
+    let drag_threshold = 5.0;  // Same as `select_turrets`: anything longer is a box selection
+    
+    if start.distance(cursor) >= drag_threshold {
+        return;
+    }
+    
+    // Clicks on menu buttons belong to the UI
+    if buttons.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+    
+    let obstacle_circles = obstacles.iter().map(|(obstacle, transform)| (transform.translation.truncate(), obstacle.radius));
+    let taken = emplacements.iter().map(|(transform, _)| transform.translation.truncate());
+    if !can_place_at(cursor, &bounds, &zone, obstacle_circles, taken) {
+        return;
+    }
+    
+    if let Some(reason) = tech.build_blocker(&limits, emplacements.iter().count()) {
+        info!("{reason}");
+        return;
+    }
+    
+    if currency.0 < TURRET_COST {
+        info!("Need {} credits to build a turret, have {}", TURRET_COST, currency.0);
+        return;
+    }
+    currency.0 -= TURRET_COST;
+    
+    // Number after the highest existing id so ids stay unique
+    let id = emplacements.iter().map(|(_, id)| id.0).max().unwrap_or(0) + 1;
+    match tech.selected {
+        TurretKind::Laser => spawn_turret(&mut commands, cursor, TurretId(id), TargetingMode::Closest, 1, None),
+        TurretKind::Launcher => spawn_turret(&mut commands, cursor, TurretId(id), TargetingMode::Closest, 1, Some(400.0)),
+        TurretKind::Artillery => spawn_artillery(&mut commands, cursor, TurretId(id)),
+    };
+    info!("Placed {} turret #{} at {:.0}", tech.selected.label(), id, cursor);
+    tutorial.complete(TutorialStep::PlaceTurret);
+}
+
+/// Whether a turret can be built at `position`: on the playfield, outside the protected
+/// zone, off every obstacle and spaced from every existing emplacement
+///
+/// Affordability is checked separately, so a build that is only short of credits can say so.
+fn can_place_at(
+    position: Vec2,
+    bounds: &WorldBounds,
+    zone: &ProtectZone,
+    mut obstacles: impl Iterator<Item = (Vec2, f32)>,
+    mut emplacements: impl Iterator<Item = Vec2>,
+) -> bool {
+    let half_base = 10.0;  // Half the square turret base
+    position.abs().cmple(bounds.half_extents() - half_base).all()
+        && !zone.contains(position)
+        && obstacles.all(|(center, radius)| center.distance(position) >= radius + half_base)
+        && emplacements.all(|other| other.distance(position) >= MIN_TURRET_SPACING)
+}
+
+// ===== BUILD MODE =====
+
+/// Turret placement mode: the simulation slows down and a ghost turret follows the cursor
+#[derive(Resource)]
+struct BuildMode {
+    active: bool,
+    time_scale: f32,   // Simulation speed while building
+}
+
+impl Default for BuildMode {
+    fn default() -> Self {
+        Self {
+            active: false,
+            time_scale: 0.2,  // A fifth of normal speed
+        }
+    }
+}
+
+/// Translucent turret base previewing where a click in build mode would build
+#[derive(Component, Default)]
+struct BuildGhost {
+    valid: bool,  // Whether a turret could be built here right now, affordability, cap and unlocks included
+}
+
+/// Toggle build mode with E, leaving it automatically once the game is no longer in play
+///
+/// The slow-down itself is applied by `apply_time_scale`.
+fn toggle_build_mode(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    visual_assets: Res<VisualAssets>,
+    mut build: ResMut<BuildMode>,
+    ghosts: Query<Entity, With<BuildGhost>>,
+) {
+    let in_play = matches!(state.get(), GameState::Running | GameState::Paused);
+    let wanted = in_play && (build.active != keyboard.just_pressed(KeyCode::KeyE));
+    if wanted == build.active {
+        return;
+    }
+    build.active = wanted;
+    
+    if build.active {
+        commands.spawn((
+            Mesh2d(visual_assets.turret_base_mesh.clone()),
+            MeshMaterial2d(visual_assets.ghost_valid_material.clone()),
+            Transform::from_xyz(0.0, 0.0, -0.9),  // Over turrets, under boids
+            Visibility::Hidden,                    // Until the cursor is over the playfield
+            BuildGhost::default(),
+        ));
+    } else {
+        for entity in &ghosts {
+            commands.entity(entity).despawn();
+        }
+    }
+    info!("Build mode {}", if build.active { "on" } else { "off" });
+}
+
+/// Move the build ghost to the cursor, hiding it while the cursor is off the window
+fn move_build_ghost(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<BuildGhost>>,
+) {
+    let Ok((mut transform, mut visibility)) = ghosts.single_mut() else { return; };
+    
+    let cursor = window_query
+        .single()
+        .ok()
+        .zip(camera_query.single().ok())
+        .and_then(|(window, (camera, camera_transform))| cursor_world_position(window, camera, camera_transform));
+    let Some(cursor) = cursor else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+    transform.translation = cursor.extend(transform.translation.z);
+}
+
+/// Tint the build ghost green where a turret could be built and red where not
+fn update_build_ghost(
+    visual_assets: Res<VisualAssets>,
+    currency: Res<Currency>,
+    (bounds, zone): (Res<WorldBounds>, Res<ProtectZone>),
+    emplacements: Query<&Transform, (With<TurretId>, Without<BuildGhost>)>,
+    obstacles: Query<(&Obstacle, &Transform), Without<BuildGhost>>,
+    mut ghosts: Query<(&mut BuildGhost, &Transform, &mut MeshMaterial2d<ColorMaterial>)>,
+    (limits, tech): (Res<BuildLimits>, Res<TechProgress>),
+) {
+    let Ok((mut ghost, transform, mut material)) = ghosts.single_mut() else { return; };
+    let position = transform.translation.truncate();
+    
+    let obstacle_circles = obstacles.iter().map(|(obstacle, transform)| (transform.translation.truncate(), obstacle.radius));
+    let taken = emplacements.iter().map(|transform| transform.translation.truncate());
+    ghost.valid = currency.0 >= TURRET_COST
+        && tech.build_blocker(&limits, emplacements.iter().count()).is_none()
+        && can_place_at(position, &bounds, &zone, obstacle_circles, taken);
+    let wanted = if ghost.valid { &visual_assets.ghost_valid_material } else { &visual_assets.ghost_invalid_material };
+    if material.0 != *wanted {
+        material.0 = wanted.clone();
+    }
+}
+
+/// Apply a command to every selected turret: T cycles targeting, A toggles
+/// preferring approaching boids, U upgrades, Delete sells
+fn batch_turret_commands(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut selected: Query<(Entity, &mut Turret, &TurretId), With<Selected>>,
+    mut currency: ResMut<Currency>,
+    visual_assets: Res<VisualAssets>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        // Every selected turret switches to the same mode, following the first one
+        let Some(mode) = selected.iter().next().map(|(_, turret, _)| turret.targeting.next()) else { return; };
+        for (_, mut turret, _) in &mut selected {
+            turret.targeting = mode;
+            turret.targets.clear();  // Re-acquire under the new mode
+        }
+        info!("Selected turrets now target {:?}", mode);
+    }
+    
+    if keyboard.just_pressed(KeyCode::KeyA) {
+        // Same rule as T: follow the first turret so the selection ends up consistent
+        let Some(prefer) = selected.iter().next().map(|(_, turret, _)| !turret.prefer_approaching) else { return; };
+        for (_, mut turret, _) in &mut selected {
+            turret.prefer_approaching = prefer;
+        }
+        info!("Selected turrets prefer approaching boids: {}", prefer);
+    }
+    
+    if keyboard.just_pressed(KeyCode::KeyU) {
+        // Upgrade in id order until the credits run out
+        let mut upgradable: Vec<_> = selected.iter_mut().filter(|(_, turret, _)| turret.level < Turret::MAX_LEVEL).collect();
+        upgradable.sort_by_key(|(_, _, turret_id)| turret_id.0);
+        for (entity, mut turret, turret_id) in upgradable {
+            let cost = turret.upgrade_cost();
+            if currency.0 < cost {
+                info!("Need {} credits to upgrade turret #{}, have {}", cost, turret_id.0, currency.0);
+                break;
+            }
+            currency.0 -= cost;
+            turret.upgrade();
+            let material = visual_assets.upgraded_turret_materials[turret.level as usize - 2].clone();
+            commands.entity(entity).try_insert(MeshMaterial2d(material));
+            info!("Upgraded turret #{} to level {}", turret_id.0, turret.level);
+        }
+    }
+    
+    if keyboard.just_pressed(KeyCode::Delete) {
+        for (entity, turret, turret_id) in &selected {
+            let refund = turret.sell_value();
+            currency.0 += refund;
+            info!("Sold turret #{} for {} credits", turret_id.0, refund);
+            commands.entity(entity).despawn();  // Its laser is cleaned up by `update_lasers`
+        }
+    }
+}
+
+/// Show combined stats for the selected turrets
+fn update_selection_summary(
+    selected: Query<(&Turret, &TurretId, &TurretStats), With<Selected>>,
+    mut summary: Query<&mut Text, With<SelectionSummary>>,
+) {
+    let Ok(mut text) = summary.single_mut() else { return; };
+    
+    let count = selected.iter().count();
+    let new_text = if count == 0 {
+        String::new()
+    } else {
+        // How many selected turrets use each mode, skipping unused ones
+        let modes: Vec<String> = TargetingMode::ALL
+            .iter()
+            .map(|&mode| (mode, selected.iter().filter(|(turret, _, _)| turret.targeting == mode).count()))
+            .filter(|&(_, used)| used > 0)
+            .map(|(mode, used)| format!("{}: {}", mode.label(), used))
+            .collect();
+        let kills: u32 = selected.iter().map(|(_, _, stats)| stats.kills).sum();
+        let damage: f32 = selected.iter().map(|(_, _, stats)| stats.damage_dealt).sum();
+        
+        // Lifetime stats per turret, in id order, for the first few selected
+        let max_listed = 5;
+        let mut per_turret: Vec<(&TurretId, &TurretStats)> = selected.iter().map(|(_, id, stats)| (id, stats)).collect();
+        per_turret.sort_by_key(|(id, _)| id.0);
+        let mut lines: Vec<String> = per_turret
+            .iter()
+            .take(max_listed)
+            .map(|(id, stats)| format!("#{}: {}", id.0, stats.describe()))
+            .collect();
+        if count > max_listed {
+            lines.push(format!("...and {} more", count - max_listed));
+        }
+        
+        format!(
+            "{} turret(s) selected\n{}\nKills: {}  Damage: {:.1}\n{}\n[T] cycle targeting  [A] prefer approaching  [U] upgrade  [Del] sell",
+            count,
+            modes.join("  "),
+            kills,
+            damage,
+            lines.join("\n"),
+        )
+    };
+    
+    // Only write on change so the text isn't re-laid out every frame
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+/// Translucent disc showing the range of the turret under the cursor
+#[derive(Component)]
+struct RangeIndicator;
+
+/// Show the range of the turret under the cursor, removing it once the cursor leaves
+#[allow(clippy::type_complexity)]
+fn show_hovered_turret_range(
+    mut commands: Commands,
+    visual_assets: Res<VisualAssets>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    turrets: Query<(&Turret, &Transform)>,
+    mut indicators: Query<(Entity, &mut Transform), (With<RangeIndicator>, Without<Turret>)>,
+) {
+    let hover_half_size = 10.0;  // Half the turret base's side length
+    
+    let cursor = window_query
+        .single()
+        .ok()
+        .zip(camera_query.single().ok())
+        .and_then(|(window, (camera, camera_transform))| cursor_world_position(window, camera, camera_transform));
+    
+    // Nearest turret whose base the cursor is over
+    let hovered = cursor.and_then(|cursor| {
+        turrets
+            .iter()
+            .map(|(turret, transform)| (turret, transform.translation.truncate()))
+            .filter(|(_, pos)| (*pos - cursor).abs().max_element() <= hover_half_size)
+            .min_by(|(_, a), (_, b)| a.distance_squared(cursor).total_cmp(&b.distance_squared(cursor)))
+    });
+    
+    let Some((turret, pos)) = hovered else {
+        for (entity, _) in &indicators {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+    
+    let wanted = Transform::from_translation(pos.extend(-1.2))  // Behind the turret bases
+        .with_scale(Vec3::splat(turret.range));
+    if let Ok((_, mut transform)) = indicators.single_mut() {
+        if *transform != wanted {
+            *transform = wanted;
+        }
+    } else {
+        commands.spawn((
+            Mesh2d(visual_assets.unit_circle_mesh.clone()),
+            MeshMaterial2d(visual_assets.range_material.clone()),
+            wanted,
+            RangeIndicator,
+        ));
+    }
+}
+
+// ===== TECH TIERS =====
+
+/// Turret kinds the build menu offers, lowest tier first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum TurretKind {
+    Laser,      // Always available
+    Launcher,   // Homing rounds
+    Artillery,  // Shells dense clusters
+}
+
+impl TurretKind {
+    /// Every kind, in build menu order
+    const ALL: [TurretKind; 3] = [TurretKind::Laser, TurretKind::Launcher, TurretKind::Artillery];
+    
+    /// Name for the build menu and logs
+    fn label(self) -> &'static str {
+        match self {
+            TurretKind::Laser => "Laser",
+            TurretKind::Launcher => "Launcher",
+            TurretKind::Artillery => "Artillery",
+        }
+    }
+}
+
+/// Build rules: how many turrets may stand at once and when the higher tiers unlock
+#[derive(Resource)]
+struct BuildLimits {
+    max_turrets: usize,                    // Emplacements allowed at once, the starting layout and artillery included
+    unlocks: Vec<(TurretKind, u32, f32)>,  // (kind, wave, score): reaching either unlocks the kind; unlisted kinds start unlocked
+}
+
+impl Default for BuildLimits {
+    fn default() -> Self {
+        Self {
+            max_turrets: 12,  // Six over the starting layout, so the map can't be walled off
+            unlocks: vec![
+                (TurretKind::Launcher, 2, 1000.0),
+                (TurretKind::Artillery, 3, 3000.0),
+            ],
+        }
+    }
+}
+
+/// Turret kinds unlocked so far this game, and the one build mode places
+#[derive(Resource)]
+struct TechProgress {
+    unlocked: HashSet<TurretKind>,
+    selected: TurretKind,
+}
+
+impl Default for TechProgress {
+    fn default() -> Self {
+        Self {
+            unlocked: HashSet::from([TurretKind::Laser]),
+            selected: TurretKind::Laser,
+        }
+    }
+}
+
+impl TechProgress {
+    /// Why the selected kind can't be built with `placed` emplacements on the map, if it can't
+    ///
+    /// Placement and credits are checked separately, as for any build.
+    fn build_blocker(&self, limits: &BuildLimits, placed: usize) -> Option<String> {
+        if !self.unlocked.contains(&self.selected) {
+            Some(format!("{} turrets are still locked", self.selected.label()))
+        } else if placed >= limits.max_turrets {
+            Some(format!("Turret limit of {} reached", limits.max_turrets))
+        } else {
+            None
+        }
+    }
+}
+
+/// Unlock each turret kind once its wave or score is reached
+fn unlock_turret_kinds(
+    limits: Res<BuildLimits>,
+    waves: Res<WaveState>,
+    score: Res<Score>,
+    mut tech: ResMut<TechProgress>,
+) {
+    for &(kind, wave, points) in &limits.unlocks {
+        if !tech.unlocked.contains(&kind) && (waves.wave >= wave || score.points >= points) {
+            tech.unlocked.insert(kind);
+            info!("{} turrets unlocked", kind.label());
+        }
+    }
+}
+
+/// Build menu row, shown while in build mode
+#[derive(Component)]
+struct BuildMenu;
+
+/// Build menu entry that picks a turret kind
+#[derive(Component)]
+struct BuildMenuButton(TurretKind);
+
+/// Turret count readout at the end of the build menu
+#[derive(Component)]
+struct TurretCountText;
+
+fn setup_build_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(190.0),                  // Above the minimap
+                left: Val::Px(40.0),                     // 40px from left
+                column_gap: Val::Px(10.0),
+                align_items: AlignItems::Center,
+                display: Display::None,                  // Until build mode is on
+                ..default()
+            },
+            BuildMenu,
+        ))
+        .with_children(|parent| {
+            for kind in TurretKind::ALL {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(130.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                        BorderColor(Color::NONE),
+                        BuildMenuButton(kind),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Text::new(kind.label()),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TurretCountText,
+            ));
+        });
+}
+
+/// Show the build menu in build mode, pick kinds on click and gray out the locked ones
+#[allow(clippy::too_many_arguments)]
+fn update_build_menu(
+    build: Res<BuildMode>,
+    limits: Res<BuildLimits>,
+    mut tech: ResMut<TechProgress>,
+    mut menus: Query<&mut Node, With<BuildMenu>>,
+    mut buttons: Query<(&Interaction, &BuildMenuButton, &Children, &mut BorderColor)>,
+    mut texts: Query<&mut TextColor>,
+    mut count_text: Query<&mut Text, With<TurretCountText>>,
+    emplacements: Query<(), With<TurretId>>,
+) {
+    for mut node in &mut menus {
+        node.display = if build.active { Display::Flex } else { Display::None };
+    }
+    if !build.active {
+        return;
+    }
+    
+    for (interaction, button, _, _) in &buttons {
+        if *interaction == Interaction::Pressed && tech.unlocked.contains(&button.0) && tech.selected != button.0 {
+            tech.selected = button.0;
+        }
+    }
+    
+    for (interaction, button, children, mut border) in &mut buttons {
+        let (text_color, border_color) = if !tech.unlocked.contains(&button.0) {
+            (Color::srgb(0.4, 0.4, 0.4), Color::NONE)                  // Locked: grayed out
+        } else if tech.selected == button.0 {
+            (Color::WHITE, Color::srgb(1.0, 0.85, 0.3))                // Picked: gold outline
+        } else if *interaction == Interaction::Hovered {
+            (Color::srgb(0.8, 0.8, 0.8), Color::NONE)
+        } else {
+            (Color::WHITE, Color::NONE)
+        };
+        border.0 = border_color;
+        for child in children.iter() {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0 = text_color;
+            }
+        }
+    }
+    
+    let new_text = format!("Turrets: {}/{}", emplacements.iter().count(), limits.max_turrets);
+    for mut text in &mut count_text {
+        if text.0 != new_text {
+            text.0 = new_text.clone();
+        }
+    }
+}
+
+// ===== CAMERA CONTROL =====
+
+/// Pan the camera with the arrow keys and zoom with the mouse wheel
+///
+/// WASD isn't used because A already toggles turret preferences. Zoom is clamped to
+/// 0.5x-3x and the view can't be panned past the playfield edges. This is only a view
+/// change: edge avoidance, spawning and the goal zone all use the playfield size from
+/// `WorldBounds`, so boids turn at the same place whatever the camera shows.
+fn camera_control(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    scroll: Res<bevy::input::mouse::AccumulatedMouseScroll>,
+    bounds: Res<WorldBounds>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    time: Res<Time<Real>>,  // Keeps working while the game is paused
+) {
+    let Ok((mut transform, mut projection)) = cameras.single_mut() else { return; };
+    let Projection::Orthographic(orthographic) = &*projection else { return; };
+    let mut scale = orthographic.scale;
+    
+    let pan_speed = 600.0;     // Screen pixels per second, so panning feels the same at any zoom
+    let zoom_step = 0.1;       // Scale change per wheel notch
+    let (min_zoom, max_zoom) = (0.5, 3.0);
+    
+    if scroll.delta.y != 0.0 {
+        // Wheel up zooms in (smaller scale); only write on change so the projection isn't flagged every frame
+        scale = (scale * (1.0 - zoom_step * scroll.delta.y.signum())).clamp(min_zoom, max_zoom);
+        if let Projection::Orthographic(orthographic) = projection.as_mut()
+            && orthographic.scale != scale
+        {
+            orthographic.scale = scale;
+        }
+    }
+    
+    let mut direction = Vec2::ZERO;
+    if keyboard.pressed(KeyCode::ArrowLeft) {
+        direction.x -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::ArrowRight) {
+        direction.x += 1.0;
+    }
+    if keyboard.pressed(KeyCode::ArrowDown) {
+        direction.y -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::ArrowUp) {
+        direction.y += 1.0;
+    }
+    let offset = direction.normalize_or_zero() * pan_speed * scale * time.delta_secs();
+    
+    // Keep the view over the playfield: no panning at all once it fits on screen
+    let half = bounds.size / 2.0;
+    let bound = half * (1.0 - scale).max(0.0);
+    let center = (transform.translation.truncate() + offset).clamp(-bound, bound);
+    if center != transform.translation.truncate() {
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+// ===== ASPECT RATIO LOCK =====
+
+/// Keeps the playfield at a fixed aspect ratio, letterboxing the rest of the window
+#[derive(Resource)]
+struct AspectLock {
+    enabled: bool,
+    aspect: f32,   // Width divided by height
+}
+
+impl Default for AspectLock {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            aspect: 16.0 / 9.0,
+        }
+    }
+}
+
+impl AspectLock {
+    /// World-space size of the playfield inside the given window
+    fn playfield(&self, window: &Window) -> Vec2 {
+        let window_size = window.size();
+        if self.enabled {
+            letterbox_size(window_size, self.aspect)
+        } else {
+            window_size
+        }
+    }
+}
+
+/// Largest size with the given aspect ratio that fits inside `available`
+fn letterbox_size(available: Vec2, aspect: f32) -> Vec2 {
+    if available.y <= 0.0 {
+        return available;  // Minimized window
+    }
+    if available.x / available.y > aspect {
+        Vec2::new(available.y * aspect, available.y)  // Too wide: bars left and right
+    } else {
+        Vec2::new(available.x, available.x / aspect)  // Too tall: bars top and bottom
+    }
+}
+
+/// Size of the playfield the simulation runs in, centered on the origin
+///
+/// The one place the window size reaches the simulation: `update_world_bounds`
+/// copies it in from the primary window, and every other system reads this instead
+/// of querying the window. Headless runs have no window and keep the default.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+struct WorldBounds {
+    size: Vec2,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self { size: Vec2::new(1920.0, 1080.0) }
+    }
+}
+
+impl WorldBounds {
+    fn half_extents(&self) -> Vec2 {
+        self.size / 2.0
+    }
+}
+
+/// Copy the primary window's playfield size into `WorldBounds`
+///
+/// Without a window (e.g. during teardown), or while it's minimized to nothing,
+/// the last good bounds are kept so the flock isn't squeezed onto a point.
+fn update_world_bounds(
+    aspect_lock: Res<AspectLock>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut bounds: ResMut<WorldBounds>,
+) {
+    let window = match window_query.single() {
+        Ok(window) => window,
+        Err(error) => {
+            warn_once!("No primary window, keeping world bounds at {}: {error}", bounds.size);
+            return;
+        }
+    };
+    let size = aspect_lock.playfield(window);
+    if size.min_element() > 0.0 {
+        bounds.set_if_neq(WorldBounds { size });
+    }
+}
+
+/// Background quad covering exactly the playfield
+#[derive(Component)]
+struct PlayfieldBackground;
+
+/// Restrict the camera to the centered playfield and size the background to match
+fn apply_letterbox(
+    aspect_lock: Res<AspectLock>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut Camera, With<Camera2d>>,
+    mut backgrounds: Query<&mut Sprite, With<PlayfieldBackground>>,
+) {
+    let Ok(window) = window_query.single() else { return; };
+    let playfield = aspect_lock.playfield(window);
+    
+    // Viewports are in physical pixels, centered in the window
+    let window_physical = window.physical_size();
+    let size = (playfield * window.scale_factor()).round().as_uvec2().min(window_physical);
+    let viewport = (aspect_lock.enabled && size != window_physical && size.x > 0 && size.y > 0)
+        .then(|| bevy::render::camera::Viewport {
+            physical_position: (window_physical - size) / 2,
+            physical_size: size,
+            ..default()
+        });
+    
+    for mut camera in &mut cameras {
+        // Only write on change so the camera isn't flagged every frame
+        let current = camera.viewport.as_ref().map(|v| (v.physical_position, v.physical_size));
+        let wanted = viewport.as_ref().map(|v| (v.physical_position, v.physical_size));
+        if current != wanted {
+            camera.viewport = viewport.clone();
+        }
+    }
+    
+    for mut sprite in &mut backgrounds {
+        if sprite.custom_size != Some(playfield) {
+            sprite.custom_size = Some(playfield);
+        }
+    }
+}
+
+/// Pull boids left outside the playfield back in after it shrinks
+///
+/// Edge steering would eventually turn them around, but until then they'd be
+/// off screen and out of turret reach.
+fn nudge_boids_into_bounds(bounds: Res<WorldBounds>, mut boids: Query<&mut Transform, With<Boid>>) {
+    if !bounds.is_changed() {
+        return;
+    }
+    let inset = 20.0;  // Land a little inside the edge, not right on it
+    let half_extents = (bounds.half_extents() - inset).max(Vec2::ZERO);
+    
+    for mut transform in &mut boids {
+        let position = transform.translation.truncate();
+        let clamped = position.clamp(-half_extents, half_extents);
+        if clamped != position {
+            transform.translation.x = clamped.x;
+            transform.translation.y = clamped.y;
+        }
+    }
+}
+
+// ===== SCREENSHOTS =====
+
+/// Freeze the simulation and save a screenshot with a stats note on F12
+///
+/// Pressing F12 again while frozen resumes. Files land in the working directory as
+/// `screenshot-<unix time>-seed<S>-frame<N>.png` next to a `.txt` with the numbers at capture time.
+#[allow(clippy::too_many_arguments)]
+fn capture_screenshot(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    frame_count: Res<bevy::diagnostic::FrameCount>,
+    mut time: ResMut<Time<Virtual>>,
+    zone: Res<ProtectZone>,
+    integrator: Res<Integrator>,
+    game_rng: Res<GameRng>,
+    boids: Query<(), With<Boid>>,
+    turret_stats: Query<&TurretStats>,
+) {
+    if !keyboard.just_pressed(KeyCode::F12) {
+        return;
+    }
+    if time.is_paused() {
+        time.unpause();
+        return;
+    }
+    time.pause();
+    
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let stem = screenshot_stem(timestamp, game_rng.seed, frame_count.0);
+    let kills: u32 = turret_stats.iter().map(|stats| stats.kills).sum();
+    let notes = format!(
+        "seed: {}\nframe: {}\nboids: {}\nkills: {}\nleaks: {}/{}\nintegrator: {:?}\n",
+        game_rng.seed,
+        frame_count.0,
+        boids.iter().count(),
+        kills,
+        zone.leaks,
+        zone.max_leaks,
+        *integrator,
+    );
+    
+    let image_path = format!("{stem}.png");
+    commands
+        .spawn(bevy::render::view::screenshot::Screenshot::primary_window())
+        .observe(move |trigger: Trigger<bevy::render::view::screenshot::ScreenshotCaptured>| {
+            // Encoding a full-resolution PNG takes a while, so keep it off the main thread
+            let image = trigger.event().0.clone();
+            let image_path = image_path.clone();
+            let notes_path = format!("{stem}.txt");
+            let notes = notes.clone();
+            bevy::tasks::IoTaskPool::get()
+                .spawn(async move { save_screenshot(image, &image_path, &notes_path, &notes) })
+                .detach();
+        });
+}
+
+/// File name, without extension, for a screenshot taken at `frame` of a run seeded with `seed`
+fn screenshot_stem(timestamp: u64, seed: u64, frame: u32) -> String {
+    format!("screenshot-{timestamp}-seed{seed}-frame{frame}")
+}
+
+/// Write a captured screenshot as a PNG and its stats note next to it, logging any failure
+fn save_screenshot(image: Image, image_path: &str, notes_path: &str, notes: &str) {
+    match image.try_into_dynamic() {
+        Ok(image) => match image.to_rgb8().save(image_path) {
+            Ok(()) => info!("Screenshot saved to {}", image_path),
+            Err(err) => error!("Cannot save screenshot: {err}"),
+        },
+        Err(err) => error!("Cannot convert screenshot: {err}"),
+    }
+    if let Err(err) = std::fs::write(notes_path, notes) {
+        error!("Cannot save screenshot notes: {err}");
+    }
+}
+
+// ===== RECORDING =====
+
+/// One boid's state in a recorded frame
+struct RecordedBoid {
+    id: BoidId,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+/// Recent boid states, buffered every running frame and written to CSV on F9
+///
+/// Only the last `max_frames` frames are kept, so leaving it running doesn't grow memory.
+#[derive(Resource)]
+struct Recorder {
+    frames: VecDeque<(u32, Vec<RecordedBoid>)>,  // (frame number, every boid that frame), oldest first
+    max_frames: usize,                            // 0 turns recording off
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            max_frames: 600,  // 10 s at 60 FPS
+        }
+    }
+}
+
+/// Append the current position and velocity of every boid to the recorder
+fn record_boid_states(
+    mut recorder: ResMut<Recorder>,
+    frame_count: Res<bevy::diagnostic::FrameCount>,
+    boids: Query<(&BoidId, &Boid, &Transform)>,
+) {
+    if recorder.max_frames == 0 {
+        return;
+    }
+    while recorder.frames.len() >= recorder.max_frames {
+        recorder.frames.pop_front();
+    }
+    
+    let states = boids
+        .iter()
+        .map(|(id, boid, transform)| RecordedBoid {
+            id: *id,
+            position: transform.translation.truncate(),
+            velocity: boid.velocity,
+        })
+        .collect();
+    recorder.frames.push_back((frame_count.0, states));
+}
+
+/// Write the buffered frames to `recording-<unix time>.csv` on F9 and start a fresh buffer
+///
+/// Rows are `frame,entity,x,y,vx,vy`; `entity` is the boid's `BoidId`, which unlike
+/// the ECS entity is never reused, so each boid's track can be followed across frames.
+fn dump_recording(keyboard: Res<ButtonInput<KeyCode>>, mut recorder: ResMut<Recorder>) {
+    if !keyboard.just_pressed(KeyCode::F9) {
+        return;
+    }
+    if recorder.frames.is_empty() {
+        info!("Nothing recorded yet");
+        return;
+    }
+    
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let path = format!("recording-{timestamp}.csv");
+    let frames = std::mem::take(&mut recorder.frames);
+    
+    // Formatting thousands of rows takes a moment, so do it with the write off the main thread
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            let mut csv = String::from("frame,entity,x,y,vx,vy\n");
+            for (frame, boids) in &frames {
+                for boid in boids {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{}\n",
+                        frame, boid.id.0, boid.position.x, boid.position.y, boid.velocity.x, boid.velocity.y,
+                    ));
+                }
+            }
+            match std::fs::write(&path, csv) {
+                Ok(()) => info!("Saved {} recorded frames to {}", frames.len(), path),
+                Err(err) => error!("Cannot save recording: {err}"),
+            }
+        })
+        .detach();
+}
+
+// ===== ECONOMY =====
+
+/// Credits earned by killing boids and spent on building turrets
+#[derive(Resource)]
+struct Currency(u32);
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self(100)  // Enough for two turrets up front
+    }
+}
+
+/// Currency readout in the top left corner
+#[derive(Component)]
+struct CurrencyText;
+
+/// Pay out for each turret kill
+///
+/// `BoidDespawned` fires once per boid (the damage systems skip boids already at zero
+/// health), so the bounty can't be paid twice when several turrets share a target.
+fn earn_bounty(
+    trigger: Trigger<BoidDespawned>,
+    mut currency: ResMut<Currency>,
+    mut turrets: Query<(&TurretId, &mut TurretStats)>,
+) {
+    let bounty = 5;
+    let DespawnCause::KilledByTurret(killer) = trigger.event().cause else { return; };
+    currency.0 += bounty;
+    
+    // Credit the killer too, unless it was sold while its shot was in flight
+    if let Some((_, mut stats)) = turrets.iter_mut().find(|(id, _)| **id == killer) {
+        stats.credits_earned += bounty;
+    }
+}
+
+fn setup_currency_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),                      // 40px from top
+            left: Val::Px(40.0),                     // 40px from left
+            ..default()
+        },
+        CurrencyText,
+    ));
+}
+
+fn update_currency_hud(currency: Res<Currency>, mut hud: Query<&mut Text, With<CurrencyText>>) {
+    if !currency.is_changed() {
+        return;
+    }
+    for mut text in &mut hud {
+        text.0 = format!("Credits: {}", currency.0);
+    }
+}
+
+// ===== SCORE =====
+
+/// Points for kills plus a slow trickle for every second survived
+#[derive(Resource, Default)]
+struct Score {
+    points: f32,
+}
+
+impl Score {
+    /// Points gained per second while the game is running
+    const SURVIVAL_RATE: f32 = 1.0;
+    
+    /// Award the kill value for a boid of this kind
+    fn add_kill(&mut self, kind: BoidKind) {
+        self.points += Self::kill_value(kind);
+    }
+    
+    /// Points a kill of this kind is worth
+    fn kill_value(kind: BoidKind) -> f32 {
+        match kind {
+            BoidKind::Normal => 10.0,
+            BoidKind::Red => 25.0,    // Marked boids are worth more
+            BoidKind::Pink => 50.0,   // There's only one
+            BoidKind::Fast => 15.0,   // Harder to track
+            BoidKind::Tank => 30.0,   // Takes three times the damage
+            BoidKind::Splitter => 15.0,  // Each half pays out again
+        }
+    }
+}
+
+/// Score readout under the credits
+#[derive(Component)]
+struct ScoreText;
+
+fn tick_survival_score(mut score: ResMut<Score>, time: Res<Time>) {
+    score.points += Score::SURVIVAL_RATE * time.delta_secs();
+}
+
+fn setup_score_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(70.0),                      // Below the currency readout
+            left: Val::Px(40.0),                     // 40px from left
+            ..default()
+        },
+        ScoreText,
+    ));
+}
+
+fn update_score_hud(score: Res<Score>, mut hud: Query<&mut Text, With<ScoreText>>) {
+    // Survival points change every frame, but the readout only shows whole points
+    let new_text = format!("Score: {}", score.points as u32);
+    for mut text in &mut hud {
+        if text.0 != new_text {
+            text.0 = new_text.clone();
+        }
+    }
+}
+
+// ===== TIME SCALE =====
+
+/// Player-chosen simulation speed, applied to the virtual clock every system reads
+///
+/// Rendering, input and the camera keep real time.
+#[derive(Resource)]
+struct TimeScale {
+    scale: f32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+impl TimeScale {
+    /// Slowest and fastest allowed speeds; each key press halves or doubles
+    const MIN: f32 = 0.25;
+    const MAX: f32 = 4.0;
+}
+
+/// Readout of the time scale under the score
+#[derive(Component)]
+struct TimeScaleText;
+
+/// Halve the simulation speed with - and double it with =
+fn adjust_time_scale(keyboard: Res<ButtonInput<KeyCode>>, mut time_scale: ResMut<TimeScale>) {
+    let factor = if keyboard.just_pressed(KeyCode::Minus) {
+        0.5
+    } else if keyboard.just_pressed(KeyCode::Equal) {
+        2.0
+    } else {
+        return;
+    };
+    time_scale.scale = (time_scale.scale * factor).clamp(TimeScale::MIN, TimeScale::MAX);
+    info!("Time scale: {}x", time_scale.scale);
+}
+
+/// Set the virtual clock's speed from the time scale and build mode together
+fn apply_time_scale(time_scale: Res<TimeScale>, build: Res<BuildMode>, mut time: ResMut<Time<Virtual>>) {
+    if !time_scale.is_changed() && !build.is_changed() {
+        return;
+    }
+    let build_factor = if build.active { build.time_scale } else { 1.0 };
+    time.set_relative_speed(time_scale.scale * build_factor);
+}
+
+fn setup_time_scale_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 0.8, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(100.0),                     // Below the score readout
+            left: Val::Px(40.0),                     // 40px from left
+            ..default()
+        },
+        TimeScaleText,
+    ));
+}
+
+fn update_time_scale_hud(time_scale: Res<TimeScale>, mut hud: Query<&mut Text, With<TimeScaleText>>) {
+    if !time_scale.is_changed() {
+        return;
+    }
+    for mut text in &mut hud {
+        // Normal speed needs no reminder
+        text.0 = if time_scale.scale == 1.0 { String::new() } else { format!("Speed: {}x", time_scale.scale) };
+    }
+}
+
+// ===== DENSITY HEATMAP =====
+
+/// Debug overlay coloring each `SpatialGrid` cell by how many boids are in it
+#[derive(Resource)]
+struct Heatmap {
+    enabled: bool,
+    refresh_timer: Timer,   // Cells are rebuilt on this tick, not every frame
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_timer: Timer::from_seconds(0.25, TimerMode::Repeating),
+        }
+    }
+}
+
+/// One translucent heatmap square
+#[derive(Component)]
+struct HeatmapCell;
+
+/// Toggle the heatmap with H and redraw its cells a few times a second while shown
+fn update_heatmap(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut heatmap: ResMut<Heatmap>,
+    grid: Res<SpatialGrid>,
+    visual_assets: Res<VisualAssets>,
+    cells: Query<Entity, With<HeatmapCell>>,
+    time: Res<Time<Real>>,  // Keeps refreshing at any time scale
+) {
+    let toggled = keyboard.just_pressed(KeyCode::KeyH);
+    if toggled {
+        heatmap.enabled = !heatmap.enabled;
+    }
+    heatmap.refresh_timer.tick(time.delta());
+    let refresh = toggled || (heatmap.enabled && heatmap.refresh_timer.just_finished());
+    if !refresh {
+        return;
+    }
+    
+    for entity in &cells {
+        commands.entity(entity).despawn();
+    }
+    if !heatmap.enabled {
+        return;
+    }
+    
+    for (cell, indices) in &grid.cells {
+        let level = match indices.len() {
+            0 => continue,
+            1..=2 => 0,
+            3..=5 => 1,
+            6..=10 => 2,
+            _ => 3,
+        };
+        let center = (cell.as_vec2() + 0.5) * grid.cell_size;
+        commands.spawn((
+            Mesh2d(visual_assets.health_bar_mesh.clone()),  // Unit square
+            MeshMaterial2d(visual_assets.heatmap_materials[level].clone()),
+            Transform::from_translation(center.extend(-1.8))  // Behind everything but the zones
+                .with_scale(Vec3::splat(grid.cell_size)),
+            HeatmapCell,
+        ));
+    }
+}
+
+// ===== MINIMAP =====
+
+/// Panel in the bottom-left corner showing the whole playfield scaled down
+#[derive(Component)]
+struct Minimap;
+
+/// One reusable dot or square on the minimap
+///
+/// A fixed pool is spawned up front and hidden when unused, so large flocks
+/// don't spawn and despawn hundreds of UI nodes on every refresh.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MinimapMarker {
+    Boid,
+    Turret,
+}
+
+impl MinimapMarker {
+    /// Markers in the pool; anything beyond these isn't drawn
+    const MAX_BOIDS: usize = 200;
+    const MAX_TURRETS: usize = 32;
+}
+
+/// How often the minimap markers move
+#[derive(Resource)]
+struct MinimapRefresh {
+    timer: Timer,
+}
+
+impl Default for MinimapRefresh {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+        }
+    }
+}
+
+fn setup_minimap(mut commands: Commands) {
+    let width = 240.0;
+    let height = 135.0;  // Same 16:9 shape as the default playfield
+    let boid_size = 3.0;
+    let turret_size = 6.0;
+    
+    let marker = |kind: MinimapMarker| {
+        let (size, color) = match kind {
+            MinimapMarker::Boid => (boid_size, Color::srgb(0.9, 0.9, 0.9)),
+            MinimapMarker::Turret => (turret_size, Color::srgb(0.3, 0.6, 1.0)),
+        };
+        (
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(size),
+                height: Val::Px(size),
+                margin: UiRect::all(Val::Px(-size / 2.0)),  // Center on the position
+                display: Display::None,                     // Hidden until in use
+                ..default()
+            },
+            BackgroundColor(color),
+            // Round boids into dots, leave turrets square
+            if kind == MinimapMarker::Boid { BorderRadius::MAX } else { BorderRadius::ZERO },
+            kind,
+        )
+    };
+    
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),                   // 40px from bottom
+                left: Val::Px(40.0),                     // 40px from left
+                width: Val::Px(width),
+                height: Val::Px(height),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.3)),
+            Minimap,
+        ))
+        .with_children(|parent| {
+            // Turrets spawn last so they draw over the boids
+            for _ in 0..MinimapMarker::MAX_BOIDS {
+                parent.spawn(marker(MinimapMarker::Boid));
+            }
+            for _ in 0..MinimapMarker::MAX_TURRETS {
+                parent.spawn(marker(MinimapMarker::Turret));
+            }
+        });
+}
+
+/// Move the minimap markers to the current boid and turret positions a few times a second
+fn minimap_update(
+    mut refresh: ResMut<MinimapRefresh>,
+    time: Res<Time<Real>>,  // Keeps refreshing while paused or slowed down
+    bounds: Res<WorldBounds>,
+    boids: Query<&Transform, With<Boid>>,
+    turrets: Query<&Transform, (With<Turret>, Without<Boid>)>,
+    mut markers: Query<(&MinimapMarker, &mut Node)>,
+) {
+    if !refresh.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let playfield = bounds.size;
+    
+    let mut boid_positions = boids.iter().map(|transform| transform.translation.truncate());
+    let mut turret_positions = turrets.iter().map(|transform| transform.translation.truncate());
+    for (marker, mut node) in &mut markers {
+        let position = match marker {
+            MinimapMarker::Boid => boid_positions.next(),
+            MinimapMarker::Turret => turret_positions.next(),
+        };
+        let Some(position) = position else {
+            node.display = Display::None;
+            continue;
+        };
+        // World y points up but UI y points down
+        let fraction = (position / playfield + 0.5).clamp(Vec2::ZERO, Vec2::ONE);
+        node.display = Display::Flex;
+        node.left = Val::Percent(fraction.x * 100.0);
+        node.top = Val::Percent((1.0 - fraction.y) * 100.0);
+    }
+}
+
+// ===== DIAGNOSTICS OVERLAY =====
+
+/// FPS and boid count readout, hidden until F3 is pressed
+#[derive(Component)]
+struct DiagnosticsOverlay;
+
+fn setup_diagnostics_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(130.0),                     // Below the title
+            right: Val::Px(40.0),                    // 40px from right
+            ..default()
+        },
+        Visibility::Hidden,
+        DiagnosticsOverlay,
+    ));
+}
+
+fn update_diagnostics_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    diagnostics: Res<bevy::diagnostic::DiagnosticsStore>,
+    boids: Query<(), With<Boid>>,
+    clusters: Res<FlockClusters>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<DiagnosticsOverlay>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay.single_mut() else { return; };
+    
+    if keyboard.just_pressed(KeyCode::F3) {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+    if *visibility == Visibility::Hidden {
+        return;  // Skip text layout while nobody can see it
+    }
+    
+    let fps = diagnostics
+        .get(&bevy::diagnostic::FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    text.0 = format!("FPS: {:.0}\nBoids: {}\nFlocks: {}", fps, boids.iter().count(), clusters.count);
+}
+
+// ===== PAUSE =====
+
+/// Whether the simulation is advancing; rendering and input keep running either way
+///
+/// Windowed runs start in `Menu`, headless runs go straight to `Running`.
+#[derive(States, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,      // Main menu over an ambient flock; the game starts on Single Player
+    #[default]
+    Running,
+    Paused,
+    GameOver,  // A boid reached the goal zone; waits for Retry
+    Victory,   // The last wave was cleared; waits for Play Again or Main Menu
+    Restarting,  // One frame while `reset_game` clears the board and it's set up again
+}
+
+/// Centered "PAUSED" label and Restart button shown while the game is paused
+#[derive(Component)]
+struct PauseOverlay;
+
+fn toggle_pause(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.any_just_pressed([KeyCode::Escape, KeyCode::Space]) {
+        next_state.set(match state.get() {
+            GameState::Running => GameState::Paused,
+            GameState::Paused => GameState::Running,
+            GameState::Menu | GameState::GameOver | GameState::Victory | GameState::Restarting => return,  // Left by buttons or restarts only
+        });
+    }
+}
+
+fn show_pause_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            PauseOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("PAUSED"),
+                TextFont {
+                    font_size: 72.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            // Starts the game over in place, like R, without going back to the menu
+            spawn_sized_button(parent, "Restart", MenuButton::Retry, 200.0);
+        });
+}
+
+fn hide_pause_overlay(mut commands: Commands, overlays: Query<Entity, With<PauseOverlay>>) {
+    for overlay in &overlays {
+        commands.entity(overlay).despawn();
+    }
+}
+
+// ===== GAME OVER =====
+
+/// Strip along the right edge of the playfield that the flock must never reach
+#[derive(Resource)]
+struct GoalZone {
+    enabled: bool,  // Whether reaching the strip ends the game
+    width: f32,     // Depth of the strip in from the edge
+}
+
+impl Default for GoalZone {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            width: 10.0,  // Thin, so the edge avoidance force turns most boids back first
+        }
+    }
+}
+
+impl GoalZone {
+    /// World-space rectangle covered by the zone for a given playfield size
+    fn rect(&self, playfield: Vec2) -> Rect {
+        let half = playfield / 2.0;
+        Rect::new(half.x - self.width, -half.y, half.x, half.y)
+    }
+    
+    /// Whether a world position lies inside the active zone
+    fn contains(&self, position: Vec2, playfield: Vec2) -> bool {
+        self.enabled && self.rect(playfield).contains(position)
+    }
+}
+
+/// Translucent red rectangle marking the goal zone
+#[derive(Component)]
+struct GoalZoneMarker;
+
+/// Centered "GAME OVER" label and Retry button
+#[derive(Component)]
+struct GameOverOverlay;
+
+fn setup_goal_zone(mut commands: Commands) {
+    commands.spawn((
+        Sprite::from_color(Color::srgba(1.0, 0.2, 0.2, 0.2), Vec2::ONE),
+        Transform::from_xyz(0.0, 0.0, -2.0),  // Behind turrets and boids
+        GoalZoneMarker,
+    ));
+}
+
+/// Fit the goal zone rectangle to the current playfield
+fn update_goal_zone_visual(
+    goal: Res<GoalZone>,
+    bounds: Res<WorldBounds>,
+    mut markers: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<GoalZoneMarker>>,
+) {
+    let rect = goal.rect(bounds.size);
+    
+    for (mut sprite, mut transform, mut visibility) in &mut markers {
+        // Only write on change so the sprite isn't flagged every frame
+        if sprite.custom_size != Some(rect.size()) {
+            sprite.custom_size = Some(rect.size());
+        }
+        if transform.translation.truncate() != rect.center() {
+            transform.translation = rect.center().extend(transform.translation.z);
+        }
+        visibility.set_if_neq(if goal.enabled { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
+/// End the game as soon as a live boid enters the goal zone
+fn detect_goal_zone(
+    goal: Res<GoalZone>,
+    bounds: Res<WorldBounds>,
+    boids: Query<(&Boid, &Transform)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let playfield = bounds.size;
+    
+    if boids
+        .iter()
+        .any(|(boid, transform)| boid.health > 0.0 && goal.contains(transform.translation.truncate(), playfield))
+    {
+        warn!("Game over: a boid reached the goal zone");
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn show_game_over_overlay(mut commands: Commands, score: Res<Score>, turrets: Query<(&TurretId, &TurretStats)>) {
+    let summary = format!("Final score: {}{}", score.points as u32, mvp_line(turrets.iter()));
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            GameOverOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("GAME OVER"),
+                TextFont {
+                    font_size: 72.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.3, 0.3)),
+            ));
+            parent.spawn((
+                Text::new(summary),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            spawn_sized_button(parent, "Retry", MenuButton::Retry, 200.0);
+        });
+}
+
+/// "MVP turret" line for the end-of-game overlays, starting on a new line; empty if nothing scored a kill
+fn mvp_line<'a>(turrets: impl IntoIterator<Item = (&'a TurretId, &'a TurretStats)>) -> String {
+    match mvp_turret(turrets) {
+        Some((id, stats)) if stats.kills > 0 => format!("\nMVP: Turret #{} ({})", id.0, stats.describe()),
+        _ => String::new(),
+    }
+}
+
+fn hide_game_over_overlay(mut commands: Commands, overlays: Query<Entity, With<GameOverOverlay>>) {
+    for overlay in &overlays {
+        commands.entity(overlay).despawn();
+    }
+}
+
+// ===== VICTORY =====
+
+/// Centered "YOU WIN" label with replay and main menu buttons
+#[derive(Component)]
+struct VictoryOverlay;
+
+fn show_victory_overlay(
+    mut commands: Commands,
+    score: Res<Score>,
+    waves: Res<WaveState>,
+    turrets: Query<(&TurretId, &TurretStats)>,
+) {
+    let summary = format!(
+        "Survived {} waves\nFinal score: {}{}",
+        waves.wave,
+        score.points as u32,
+        mvp_line(turrets.iter()),
+    );
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            VictoryOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("YOU WIN"),
+                TextFont {
+                    font_size: 72.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.4, 1.0, 0.4)),
+            ));
+            parent.spawn((
+                Text::new(summary),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            spawn_sized_button(parent, "Play Again", MenuButton::Retry, 200.0);
+            spawn_sized_button(parent, "Main Menu", MenuButton::MainMenu, 200.0);
+        });
+}
+
+fn hide_victory_overlay(mut commands: Commands, overlays: Query<Entity, With<VictoryOverlay>>) {
+    for overlay in &overlays {
+        commands.entity(overlay).despawn();
+    }
+}
+
+/// Restart the current game from scratch on R (not from the main menu)
+fn restart_on_key(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let in_game = matches!(state.get(), GameState::Running | GameState::Paused | GameState::GameOver | GameState::Victory);
+    if in_game && keyboard.just_pressed(KeyCode::KeyR) {
+        next_state.set(GameState::Restarting);
+    }
+}
+
+/// Clear boids, turrets, gravity wells, kill feed lines and every effect, and restore the starting resources
+///
+/// Runs before `setup_boids` and `setup_turrets` rebuild the opening board, so
+/// turrets are never doubled up. Also clears the board when returning to the menu.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn reset_game(
+    mut commands: Commands,
+    entities: Query<
+        Entity,
+        Or<(
+            With<Boid>,
+            With<TurretId>,
+            With<Predator>,
+            With<LaserBeam>,
+            With<Blast>,
+            With<Particle>,
+            With<Projectile>,
+            With<Leader>,
+            With<GravityWell>,
+            With<HeatmapCell>,
+            With<Trail>,
+            With<KillFeedEntry>,
+        )>,
+    >,
+    mut zone: ResMut<ProtectZone>,
+    mut waves: ResMut<WaveState>,
+    mut currency: ResMut<Currency>,
+    mut score: ResMut<Score>,
+    mut formation: ResMut<Formation>,
+    mut curiosity: ResMut<Curiosity>,
+    mut difficulty: ResMut<Difficulty>,
+    mut panic: ResMut<PanicTimer>,
+    mut tech: ResMut<TechProgress>,
+    mut clusters: ResMut<FlockClusters>,
+    mut leader_path: ResMut<LeaderPath>,
+    // The next two are paired to stay within Bevy's 16 system parameters
+    (mut next_id, mut game_rng): (ResMut<NextBoidId>, ResMut<GameRng>),
+    (feed, recorder): (Option<ResMut<KillFeed>>, Option<ResMut<Recorder>>),  // Windowed runs only
+) {
+    for entity in &entities {
+        commands.entity(entity).despawn();
+    }
+    zone.leaks = 0;
+    // The wave target is a per-session setting, so it survives restarts
+    *waves = WaveState {
+        target_waves: waves.target_waves,
+        ..default()
+    };
+    *difficulty = Difficulty::default();
+    *panic = PanicTimer::default();
+    *tech = TechProgress::default();  // Every game climbs the tiers again
+    *currency = Currency::default();
+    *score = Score::default();
+    formation.assignments.clear();
+    curiosity.sources.clear();
+    *clusters = FlockClusters::default();
+    // Back to the start of the route; whether it's running is the player's choice
+    leader_path.anchor = leader_path.waypoints.first().copied().unwrap_or_default();
+    leader_path.next_waypoint = 1;
+    if let Some(mut feed) = feed {
+        feed.entries.clear();  // The lines themselves are despawned above
+    }
+    if let Some(mut recorder) = recorder {
+        recorder.frames.clear();
+    }
+    // Reseed and renumber so a restarted run plays out like the first one, with the same leaders
+    *next_id = NextBoidId::default();
+    game_rng.rng = StdRng::seed_from_u64(game_rng.seed);
+}
+
+/// Resume play once the board has been rebuilt
+fn finish_restart(mut next_state: ResMut<NextState<GameState>>) {
+    info!("Restarting");
+    next_state.set(GameState::Running);
+}
+
+// ===== TUTORIAL =====
+
+/// First-run tutorial steps, in order; each one is finished by doing what it asks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TutorialStep {
+    EnterBuildMode,
+    PlaceTurret,     // Completed by `place_turret`, the only system that knows a build went through
+    SelectTurret,
+    CycleTargeting,
+}
+
+impl TutorialStep {
+    /// The step after this one, or `None` once the tutorial is over
+    fn next(self) -> Option<Self> {
+        match self {
+            TutorialStep::EnterBuildMode => Some(TutorialStep::PlaceTurret),
+            TutorialStep::PlaceTurret => Some(TutorialStep::SelectTurret),
+            TutorialStep::SelectTurret => Some(TutorialStep::CycleTargeting),
+            TutorialStep::CycleTargeting => None,
+        }
+    }
+    
+    /// What the callout says
+    fn text(self) -> String {
+        match self {
+            TutorialStep::EnterBuildMode => "Press E to enter build mode. The action slows down while you pick a spot.".into(),
+            TutorialStep::PlaceTurret => format!(
+                "< Building a turret costs {TURRET_COST} credits and every kill earns more. \
+                 Click open ground, clear of rocks and the blue zone, to build one."
+            ),
+            TutorialStep::SelectTurret => "Click a turret to select it, or drag a box around several.".into(),
+            TutorialStep::CycleTargeting => {
+                "The panel on the left sums up your selection. Press T to cycle what it targets: \
+                 the closest boid, the biggest threat, the most wounded and more."
+                    .into()
+            }
+        }
+    }
+    
+    /// Where the callout sits: beside the HUD element it explains, otherwise along the bottom
+    fn callout_node(self) -> Node {
+        let along_bottom = Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        };
+        match self {
+            TutorialStep::PlaceTurret => Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(36.0),      // Level with the currency readout
+                left: Val::Px(220.0),    // Just right of it
+                ..default()
+            },
+            TutorialStep::EnterBuildMode | TutorialStep::SelectTurret | TutorialStep::CycleTargeting => along_bottom,
+        }
+    }
+}
+
+/// Progress through the first-run tutorial
+///
+/// Started with each game until it has been finished or skipped once, which is
+/// remembered in `BoidSettings::tutorial_done`.
+#[derive(Resource, Default)]
+struct Tutorial {
+    step: Option<TutorialStep>,  // None while no tutorial is showing
+    finished: bool,              // Completed or skipped this session
+}
+
+impl Tutorial {
+    /// Move on from `step` if it's the one showing; doing a later step early doesn't count
+    fn complete(&mut self, step: TutorialStep) {
+        if self.step == Some(step) {
+            self.step = step.next();
+            self.finished = self.step.is_none();
+        }
+    }
+}
+
+/// Callout panel for the current tutorial step
+#[derive(Component)]
+struct TutorialPanel;
+
+/// Begin the tutorial when a game starts, unless it was already finished in an earlier run
+fn start_tutorial(settings: Res<BoidSettings>, mut tutorial: ResMut<Tutorial>) {
+    if !settings.tutorial_done {
+        tutorial.step = Some(TutorialStep::EnterBuildMode);
+    }
+}
+
+/// Put the tutorial away on the way back to the menu; the next game starts it over
+fn stop_tutorial(mut tutorial: ResMut<Tutorial>) {
+    tutorial.step = None;
+}
+
+/// Complete the current step once the player has done it, or skip the rest on F1
+fn advance_tutorial(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    build: Res<BuildMode>,
+    selected: Query<(), With<Selected>>,
+    mut tutorial: ResMut<Tutorial>,
+    mut settings: ResMut<BoidSettings>,
+) {
+    let Some(step) = tutorial.step else { return; };
+    
+    if keyboard.just_pressed(KeyCode::F1) {
+        tutorial.step = None;
+        tutorial.finished = true;
+        info!("Tutorial skipped");
+    } else {
+        let done = match step {
+            TutorialStep::EnterBuildMode => build.active,
+            TutorialStep::PlaceTurret => false,
+            TutorialStep::SelectTurret => !selected.is_empty(),
+            TutorialStep::CycleTargeting => !selected.is_empty() && keyboard.just_pressed(KeyCode::KeyT),
+        };
+        if done {
+            tutorial.complete(step);
+        }
+    }
+    
+    // Saved with the other settings on exit
+    if tutorial.finished {
+        settings.tutorial_done = true;
+    }
+}
+
+/// Swap the callout whenever the tutorial moves on
+fn update_tutorial_panel(
+    mut commands: Commands,
+    tutorial: Res<Tutorial>,
+    panels: Query<Entity, With<TutorialPanel>>,
+) {
+    if !tutorial.is_changed() {
+        return;
+    }
+    for panel in &panels {
+        commands.entity(panel).despawn();
+    }
+    let Some(step) = tutorial.step else { return; };
+    
+    commands
+        .spawn((step.callout_node(), TutorialPanel))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        max_width: Val::Px(460.0),
+                        padding: UiRect::all(Val::Px(12.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                    BorderColor(Color::srgb(1.0, 0.85, 0.3)),  // Same gold as the credits
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text::new(step.text()),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    panel.spawn((
+                        Text::new("F1 skips the tutorial"),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    ));
+                });
+        });
+}
+
+// ===== HEADLESS MODE =====
+
+/// Periodic stats printing for `--headless` runs
+#[derive(Resource)]
+struct HeadlessStats {
+    timer: Timer,
+}
+
+impl Default for HeadlessStats {
+    fn default() -> Self {
+        Self { timer: Timer::from_seconds(1.0, TimerMode::Repeating) }
+    }
+}
+
+/// Plugins and systems that stand in for the window and menu in `--headless` runs
+fn add_headless_mode(app: &mut App) {
+    app.add_plugins((MinimalPlugins, LogPlugin::default(), bevy::state::app::StatesPlugin))
+        .init_resource::<HeadlessStats>()
+        // No menu: the game starts right away
+        .add_systems(Startup, (setup_boids, setup_turrets))
+        .add_systems(Update, print_headless_stats);
+}
+
+/// Print a one-line summary of the simulation to stdout every second
+#[allow(clippy::too_many_arguments)]
+fn print_headless_stats(
+    mut stats: ResMut<HeadlessStats>,
+    boids: Query<&Boid>,
+    turrets: Query<&TurretStats>,
+    zone: Res<ProtectZone>,
+    waves: Res<WaveState>,
+    score: Res<Score>,
+    clusters: Res<FlockClusters>,
+    time: Res<Time>,
+) {
+    stats.timer.tick(time.delta());
+    if !stats.timer.just_finished() {
+        return;
+    }
+    
+    let kills: u32 = turrets.iter().map(|turret| turret.kills).sum();
+    println!(
+        "t={:.0}s wave={} boids={} flocks={} turrets={} kills={} leaks={} score={}",
+        time.elapsed_secs(),
+        waves.wave,
+        boids.iter().count(),
+        clusters.count,
+        turrets.iter().count(),
+        kills,
+        zone.leaks,
+        score.points as u32,
+    );
+}
+
+// ===== PARAMETER SWEEP =====
+
+/// Cohesion weights tried by `--sweep`, one axis of the grid
+const SWEEP_COHESION: [f32; 5] = [0.2, 0.6, 1.0, 1.4, 1.8];
+
+/// Separation weights tried by `--sweep`, the other axis
+const SWEEP_SEPARATION: [f32; 5] = [0.5, 1.0, 1.5, 2.0, 2.5];
+
+/// Frames each combination runs for, at a fixed 60 FPS (20 simulated seconds)
+const SWEEP_TICKS: u32 = 1200;
+
+/// Weights one sweep run overrides on top of the default `BoidSettings`
+#[derive(Resource, Clone, Copy)]
+struct SweepPoint {
+    cohesion_weight: f32,
+    separation_weight: f32,
+}
+
+/// Replace whatever `load_settings` read with the defaults plus this run's weights
+fn apply_sweep_point(point: Res<SweepPoint>, mut settings: ResMut<BoidSettings>) {
+    *settings = BoidSettings {
+        cohesion_weight: point.cohesion_weight,
+        separation_weight: point.separation_weight,
+        ..default()
+    };
+}
+
+/// Run the headless game with every (cohesion, separation) pair and return the results as CSV
+///
+/// Each combination gets its own app, seeded with `seed` and stepped `ticks` times at 1/60 s,
+/// so a row can be reproduced alone and the runs go in parallel, one thread each. Rows are
+/// `cohesion_weight,separation_weight,clusters,boids,avg_speed,dispersion`:
+/// * `clusters` - separate flocks at the end (`FlockClusters`); 1 is a single flock
+/// * `boids` - boids still alive, as turrets keep thinning the flock
+/// * `avg_speed` - mean boid speed in pixels per second
+/// * `dispersion` - mean distance of a boid from the flock's centroid in pixels; high with
+///   few clusters means one loose flock, high with many means scattered groups
+fn run_sweep(cohesion: &[f32], separation: &[f32], ticks: u32, seed: u64) -> String {
+    let points: Vec<SweepPoint> = cohesion
+        .iter()
+        .flat_map(|&cohesion_weight| {
+            separation.iter().map(move |&separation_weight| SweepPoint { cohesion_weight, separation_weight })
+        })
+        .collect();
+    
+    let rows: Vec<String> = std::thread::scope(|scope| {
+        let runs: Vec<_> = points.iter().map(|&point| scope.spawn(move || run_sweep_point(point, ticks, seed))).collect();
+        runs.into_iter().map(|run| run.join().expect("sweep run panicked")).collect()
+    });
+    
+    let mut csv = String::from("cohesion_weight,separation_weight,clusters,boids,avg_speed,dispersion\n");
+    for row in rows {
+        csv.push_str(&row);
+    }
+    csv
+}
+
+/// One `run_sweep` row: the headless game (minus logging and stats) run with `point`'s weights
+fn run_sweep_point(point: SweepPoint, ticks: u32, seed: u64) -> String {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin))
+        .insert_resource(point)
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)))
+        .add_systems(PreStartup, apply_sweep_point.after(load_settings))
+        .add_systems(Startup, (setup_boids, setup_turrets));
+    add_simulation(&mut app, true, seed);
+    for _ in 0..ticks {
+        app.update();
+    }
+    
+    let world = app.world_mut();
+    let boids: Vec<(Vec2, Vec2)> = world
+        .query::<(&Transform, &Boid)>()
+        .iter(world)
+        .map(|(transform, boid)| (transform.translation.truncate(), boid.velocity))
+        .collect();
+    let count = boids.len().max(1) as f32;
+    let centroid = boids.iter().map(|(position, _)| *position).sum::<Vec2>() / count;
+    let avg_speed = boids.iter().map(|(_, velocity)| velocity.length()).sum::<f32>() / count;
+    let dispersion = boids.iter().map(|(position, _)| position.distance(centroid)).sum::<f32>() / count;
+    format!(
+        "{},{},{},{},{:.1},{:.1}\n",
+        point.cohesion_weight,
+        point.separation_weight,
+        world.resource::<FlockClusters>().count,
+        boids.len(),
+        avg_speed,
+        dispersion,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    
+    /// Every `BoidDespawned` seen, as (id, kind, cause)
+    #[derive(Resource, Default)]
+    struct Despawns(Vec<(BoidId, BoidKind, DespawnCause)>);
+    
+    fn record_despawn(trigger: Trigger<BoidDespawned>, mut despawns: ResMut<Despawns>) {
+        let event = trigger.event();
+        despawns.0.push((event.id, event.kind, event.cause));
+    }
+    
+    /// Where every `BoidKilled` happened
+    #[derive(Resource, Default)]
+    struct Kills(Vec<Vec2>);
+    
+    fn record_kill(trigger: Trigger<BoidKilled>, mut kills: ResMut<Kills>) {
+        kills.0.push(trigger.event().position);
+    }
+    
+    /// App with the shared simulation resources and a clock only `step` moves
+    fn sim_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<NextBoidId>()
+            .init_resource::<ProtectZone>()
+            .init_resource::<GoalZone>()
+            .init_resource::<Curiosity>()
+            .init_resource::<Formation>()
+            .init_resource::<Integrator>()
+            .init_resource::<BoundaryMode>()
+            .init_resource::<Resistances>()
+            .init_resource::<LeaderPath>()
+            .init_resource::<CursorForce>()
+            .init_resource::<Stamina>()
+            .init_resource::<SpatialGrid>()
+            .init_resource::<BoidSettings>()
+            .init_resource::<Currency>()
+            .init_resource::<Score>()
+            .init_resource::<WaveState>()
+            .init_resource::<Difficulty>()
+            .init_resource::<WorldBounds>()
+            .init_resource::<PanicTimer>()
+            .init_resource::<BuildLimits>()
+            .init_resource::<TechProgress>()
+            .init_resource::<FlockClusters>()
+            .init_resource::<NextState<GameState>>()
+            .insert_resource(GameRng::new(GameRng::DEFAULT_SEED));
+        app
+    }
+    
+    /// `sim_app` plus the asset storage and shared handles the drawing systems need
+    fn visual_app() -> App {
+        let mut app = sim_app();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<Image>()
+            .init_resource::<VisualSettings>()
+            .init_resource::<VisualAssets>();
+        app
+    }
+    
+    /// Advance the clock by `seconds` and run one frame
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+    
+    /// Spawn a full-health boid of the given kind on team 0
+    fn spawn_boid(app: &mut App, kind: BoidKind, position: Vec2, velocity: Vec2) -> Entity {
+        let id = app.world_mut().resource_mut::<NextBoidId>().next();
+        app.world_mut()
+            .spawn((
+                Boid {
+                    velocity,
+                    acceleration: Vec2::ZERO,
+                    health: kind.max_health(),
+                    max_health: kind.max_health(),
+                    max_speed: BoidSettings::default().max_speed * kind.speed_scale(),
+                    energy: 1.0,
+                    damage_flash_timer: Timer::from_seconds(0.5, TimerMode::Once),
+                    team: 0,
+                    generation: 0,
+                },
+                kind,
+                id,
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+    
+    /// Snapshot every boid into the spatial grid, as `update_boids` does each frame
+    fn index_boids(app: &mut App) {
+        let world = app.world_mut();
+        let boids: Vec<_> = world
+            .query::<(&Boid, &Transform, Entity, &BoidKind)>()
+            .iter(world)
+            .map(|(boid, transform, entity, kind)| (transform.translation.truncate(), boid.velocity, entity, boid.team, *kind))
+            .collect();
+        world.resource_mut::<SpatialGrid>().rebuild(boids.into_iter());
+    }
+    
+    /// Spawn a turret through `spawn_turret` with its acquisition delay and reload already run down
+    fn spawn_ready_turret(
+        app: &mut App,
+        position: Vec2,
+        id: TurretId,
+        targeting: TargetingMode,
+        max_targets: usize,
+        projectile_speed: Option<f32>,
+    ) -> Entity {
+        let entity = app
+            .world_mut()
+            .run_system_once(move |mut commands: Commands| {
+                spawn_turret(&mut commands, position, id, targeting, max_targets, projectile_speed)
+            })
+            .unwrap();
+        let mut turret = app.world_mut().get_mut::<Turret>(entity).unwrap();
+        let cooldown = turret.cooldown_timer.duration();
+        turret.cooldown_timer.tick(cooldown);
+        let reload = turret.fire_timer.duration();
+        turret.fire_timer.tick(reload);
+        entity
+    }
+    
+    /// Point a turret's barrel at these targets, skipping `update_turrets`
+    fn lock_on(app: &mut App, turret: Entity, targets: &[Entity]) {
+        let mut turret = app.world_mut().get_mut::<Turret>(turret).unwrap();
+        turret.targets = targets.to_vec();
+        turret.on_target = true;
+    }
+    
+    /// Line up a one-shot kill: a nearly dead boid of `kind` in front of a locked-on turret `id`
+    fn line_up_kill(app: &mut App, kind: BoidKind, id: TurretId) -> Entity {
+        let boid = spawn_boid(app, kind, Vec2::new(0.0, 100.0), Vec2::ZERO);
+        app.world_mut().get_mut::<Boid>(boid).unwrap().health = 0.01;
+        let turret = spawn_ready_turret(app, Vec2::ZERO, id, TargetingMode::Closest, 1, None);
+        lock_on(app, turret, &[boid]);
+        boid
+    }
+    
+    #[test]
+    fn turret_kill_fires_despawn_event_with_turret_cause() {
+        let mut app = sim_app();
+        app.init_resource::<Despawns>()
+            .add_observer(record_despawn)
+            .add_systems(Update, apply_laser_damage);
+        let boid = line_up_kill(&mut app, BoidKind::Red, TurretId(2));
+        let boid_id = *app.world().get::<BoidId>(boid).unwrap();
+        
+        step(&mut app, 0.1);
+        
+        assert_eq!(
+            app.world().resource::<Despawns>().0,
+            [(boid_id, BoidKind::Red, DespawnCause::KilledByTurret(TurretId(2)))],
+        );
+        assert!(app.world().get_entity(boid).is_err());
+    }
+    
+    #[test]
+    fn crowded_range_shortens_acquisition_cooldown() {
+        let sparse = adaptive_cooldown(1);
+        let crowded = adaptive_cooldown(20);
+        
+        assert!(crowded < sparse, "{crowded} should be shorter than {sparse}");
+        assert_eq!(adaptive_cooldown(0), sparse);  // Empty field waits like a single boid
+        assert_eq!(adaptive_cooldown(1000), 0.1);  // Never faster than the floor
+    }
+    
+    #[test]
+    fn reversing_force_turns_at_most_max_turn_rate_per_tick() {
+        let mut app = sim_app();
+        app.world_mut().resource_mut::<BoidSettings>().max_turn_rate = 90.0;
+        app.add_systems(Update, update_boids);
+        let heading = Vec2::new(200.0, 0.0);
+        let boid = spawn_boid(&mut app, BoidKind::Normal, Vec2::ZERO, heading);
+        app.world_mut().get_mut::<Boid>(boid).unwrap().acceleration = Vec2::new(-100_000.0, 1.0);
+        
+        step(&mut app, 0.1);
+        
+        let velocity = app.world().get::<Boid>(boid).unwrap().velocity;
+        let turned = heading.angle_to(velocity).abs().to_degrees();
+        assert!(turned <= 9.0 + 1e-3, "turned {turned} degrees, allowed 9");
+        assert!(turned > 8.0, "the force should have used up the allowed turn, got {turned}");
+    }
+    
+    #[test]
+    fn turret_kill_appends_one_feed_line_naming_killer_and_victim() {
+        let mut app = sim_app();
+        app.init_resource::<KillFeed>()
+            .add_observer(push_kill_feed)
+            .add_systems(Startup, setup_kill_feed)
+            .add_systems(Update, apply_laser_damage);
+        app.update();  // Build the feed container
+        line_up_kill(&mut app, BoidKind::Red, TurretId(2));
+        
+        step(&mut app, 0.1);
+        
+        let entries = &app.world().resource::<KillFeed>().entries;
+        assert_eq!(entries.len(), 1);
+        let text = app.world().get::<Text>(entries[0]).unwrap();
+        assert_eq!(text.0, "Turret #2 killed a Red boid");
+    }
+    
+    #[test]
+    fn same_frame_spawns_keep_their_distance() {
+        let mut app = sim_app();
+        app.world_mut().resource_mut::<WaveState>().to_spawn = 5;
+        app.add_systems(Update, respawn_boids);
+        
+        step(&mut app, 0.1);  // One spawn tick: a single batch of five
+        
+        let positions: Vec<Vec2> = app
+            .world_mut()
+            .query_filtered::<&Transform, With<Boid>>()
+            .iter(app.world())
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        assert_eq!(positions.len(), 5);
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                assert!(a.distance(*b) >= MIN_SPAWN_SPACING, "{a} and {b} spawned on top of each other");
+            }
+        }
+    }
+    
+    #[test]
+    fn opening_flock_spawns_in_full_when_no_spot_is_free() {
+        let mut app = sim_app();
+        app.world_mut().resource_mut::<BoidSettings>().target_count = 1000;
+        // Far too small for 1000 spaced boids, and nowhere is clear of the goal zone
+        app.insert_resource(WorldBounds { size: Vec2::splat(100.0) });
+        
+        app.world_mut().run_system_once(setup_boids).unwrap();
+        
+        assert_eq!(app.world_mut().query::<&Boid>().iter(app.world()).count(), 1000);
+    }
+    
+    #[test]
+    fn build_ghost_validity_matches_can_place_at() {
+        let mut app = visual_app();
+        app.add_systems(Update, update_build_ghost);
+        let (turret, obstacle, obstacle_radius) = (Vec2::new(-300.0, 200.0), Vec2::new(300.0, 200.0), 40.0);
+        spawn_ready_turret(&mut app, turret, TurretId(1), TargetingMode::Closest, 1, None);
+        app.world_mut().spawn((Obstacle { radius: obstacle_radius }, Transform::from_translation(obstacle.extend(0.0))));
+        let ghost = app
+            .world_mut()
+            .spawn((BuildGhost::default(), Transform::default(), MeshMaterial2d(Handle::<ColorMaterial>::default())))
+            .id();
+        
+        let cases = [
+            (Vec2::new(200.0, -200.0), true),   // Open ground
+            (Vec2::new(10.0, 10.0), false),     // Inside the protected zone
+            (Vec2::new(320.0, 190.0), false),   // On the obstacle
+            (Vec2::new(-290.0, 200.0), false),  // On top of the turret
+            (Vec2::new(2000.0, 0.0), false),    // Off the playfield
+        ];
+        for (position, expected) in cases {
+            app.world_mut().get_mut::<Transform>(ghost).unwrap().translation = position.extend(0.0);
+            step(&mut app, 0.1);
+            
+            let world = app.world();
+            let placeable = can_place_at(
+                position,
+                world.resource::<WorldBounds>(),
+                world.resource::<ProtectZone>(),
+                [(obstacle, obstacle_radius)].into_iter(),
+                [turret].into_iter(),
+            );
+            let valid = world.get::<BuildGhost>(ghost).unwrap().valid;
+            assert_eq!(valid, placeable, "at {position}");
+            assert_eq!(valid, expected, "at {position}");
+        }
+    }
+    
+    #[test]
+    fn boid_heading_for_the_zone_is_targeted_first() {
+        let mut app = sim_app();
+        app.world_mut().resource_mut::<ProtectZone>().center = Vec2::new(0.0, -300.0);
+        app.add_systems(Update, update_turrets);
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+        // Same distance from the turret, one drifting away from the zone and one towards it
+        spawn_boid(&mut app, BoidKind::Normal, Vec2::new(-100.0, 0.0), Vec2::new(0.0, 150.0));
+        let inbound = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(100.0, 0.0), Vec2::new(0.0, -150.0));
+        index_boids(&mut app);
+        
+        step(&mut app, 0.1);
+        
+        assert_eq!(app.world().get::<Turret>(turret).unwrap().targets, [inbound]);
+    }
+    
+    #[test]
+    fn zoomed_out_camera_flags_only_distant_boids_low_detail() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_boid_lod);
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera2d,
+                Projection::Orthographic(OrthographicProjection { scale: 2.0, ..OrthographicProjection::default_2d() }),
+                Transform::default(),
+            ))
+            .id();
+        let near = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(100.0, 0.0), Vec2::ZERO);
+        let far = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(1000.0, 0.0), Vec2::ZERO);
+        
+        step(&mut app, 0.1);
+        
+        assert!(!app.world().entity(near).contains::<LowDetail>());
+        assert!(app.world().entity(far).contains::<LowDetail>());
+        
+        // Zooming back in restores full detail everywhere
+        *app.world_mut().get_mut::<Projection>(camera).unwrap() = Projection::Orthographic(OrthographicProjection::default_2d());
+        step(&mut app, 0.1);
+        
+        assert!(!app.world().entity(far).contains::<LowDetail>());
+    }
+    
+    #[test]
+    fn capped_beams_still_damage_every_target() {
+        let mut app = visual_app();
+        app.insert_resource(LaserSettings { max_rendered_beams: 1, ..default() })
+            .add_observer(spawn_laser_flash)
+            .add_systems(Update, apply_laser_damage);
+        let boids: Vec<Entity> = (0..3)
+            .map(|i| spawn_boid(&mut app, BoidKind::Tank, Vec2::new(i as f32 * 20.0, 100.0), Vec2::ZERO))
+            .collect();
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 3, None);
+        lock_on(&mut app, turret, &boids);
+        
+        step(&mut app, 0.1);
+        
+        for &boid in &boids {
+            let boid = app.world().get::<Boid>(boid).unwrap();
+            assert!(boid.health < boid.max_health, "every target takes a shot");
+        }
+        let beams = app.world_mut().query::<&LaserBeam>().iter(app.world()).count();
+        assert_eq!(beams, 1);
+    }
+    
+    #[test]
+    fn placing_a_turret_briefly_draws_nearby_boids_towards_it() {
+        // Sideways speed a boid passing a freshly placed turret picks up in one tick
+        let pull_towards_turret = |enabled: bool| {
+            let mut app = sim_app();
+            app.world_mut().resource_mut::<Curiosity>().enabled = enabled;
+            app.add_observer(spark_curiosity).add_systems(Update, update_boids);
+            let boid = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(200.0, 0.0), Vec2::new(0.0, 200.0));
+            spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+            
+            step(&mut app, 0.1);
+            
+            -app.world().get::<Boid>(boid).unwrap().velocity.x
+        };
+        
+        assert!(pull_towards_turret(true) > 0.0);
+        assert_eq!(pull_towards_turret(false), 0.0);
+    }
+    
+    #[test]
+    fn threat_targeting_prefers_the_boid_attacking_a_turret() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_turrets);
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Threat, 1, None);
+        // Same distance from the turret, one cruising past and one diving at it
+        spawn_boid(&mut app, BoidKind::Normal, Vec2::new(-100.0, 0.0), Vec2::new(0.0, 150.0));
+        let attacker = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(100.0, 0.0), Vec2::new(-150.0, 0.0));
+        index_boids(&mut app);
+        
+        step(&mut app, 0.1);
+        
+        assert_eq!(app.world().get::<Turret>(turret).unwrap().targets, [attacker]);
+    }
+    
+    #[test]
+    fn lone_boid_integrates_its_acceleration_once_per_tick() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_boids);
+        let boid = spawn_boid(&mut app, BoidKind::Normal, Vec2::ZERO, Vec2::new(150.0, 0.0));
+        app.world_mut().get_mut::<Boid>(boid).unwrap().acceleration = Vec2::new(100.0, 0.0);
+        
+        step(&mut app, 0.1);
+        
+        // (150 + 100 * 0.1) * 0.99 damping
+        let boid_state = app.world().get::<Boid>(boid).unwrap();
+        assert!(boid_state.velocity.abs_diff_eq(Vec2::new(158.4, 0.0), 1e-3), "{}", boid_state.velocity);
+        assert_eq!(boid_state.acceleration, Vec2::ZERO);
+        
+        // With nothing pushing it the next tick only damps
+        step(&mut app, 0.1);
+        
+        let velocity = app.world().get::<Boid>(boid).unwrap().velocity;
+        assert!(velocity.abs_diff_eq(Vec2::new(158.4 * 0.99, 0.0), 1e-3), "{velocity}");
+    }
+    
+    #[test]
+    fn formation_boids_converge_on_their_shape_points() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_boids);
+        let boids: Vec<(Entity, Vec2)> = (0..8)
+            .map(|i| {
+                let position = Vec2::new(-350.0 + i as f32 * 100.0, -250.0);
+                (spawn_boid(&mut app, BoidKind::Normal, position, Vec2::new(0.0, 150.0)), position)
+            })
+            .collect();
+        let points = sample_shape_points(FormationShape::Heart, 600.0, boids.len());
+        let assignments = assign_formation(&points, &boids);
+        assert_eq!(assignments.len(), boids.len());
+        app.world_mut().resource_mut::<Formation>().assignments = assignments.clone();
+        
+        for _ in 0..600 {
+            step(&mut app, 1.0 / 60.0);
+        }
+        
+        for (entity, point) in assignments {
+            let position = app.world().get::<Transform>(entity).unwrap().translation.truncate();
+            assert!(position.distance(point) < 10.0, "boid at {position} never reached {point}");
+        }
+    }
+    
+    #[test]
+    fn bundled_formation_image_yields_shape_points() {
+        let bytes = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/formation.png")).unwrap();
+        let image = Image::from_buffer(
+            &bytes,
+            bevy::image::ImageType::Extension("png"),
+            default(),
+            true,
+            bevy::image::ImageSampler::Default,
+            default(),
+        )
+        .unwrap();
+        
+        let points = sample_image_points(&image, 600.0, 100);
+        
+        assert_eq!(points.len(), 100);
+        assert!(points.iter().all(|point| point.abs().max_element() <= 300.0));
+    }
+    
+    #[test]
+    fn damage_dealt_stat_matches_health_removed_over_several_shots() {
+        let mut app = sim_app();
+        app.add_systems(Update, apply_laser_damage);
+        let boid = spawn_boid(&mut app, BoidKind::Tank, Vec2::new(0.0, 100.0), Vec2::ZERO);
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+        lock_on(&mut app, turret, &[boid]);
+        
+        let mut last_dealt = 0.0;
+        for shots in 1..=4 {
+            step(&mut app, 0.5);  // One reload per tick
+            
+            let stats = app.world().get::<TurretStats>(turret).unwrap();
+            let boid = app.world().get::<Boid>(boid).unwrap();
+            assert_eq!(stats.shots_fired, shots);
+            assert!(stats.damage_dealt > last_dealt);
+            assert!((stats.damage_dealt - (boid.max_health - boid.health)).abs() < 1e-4);
+            last_dealt = stats.damage_dealt;
+        }
+    }
+    
+    #[test]
+    fn kill_bounty_is_credited_to_the_killer() {
+        let mut app = sim_app();
+        app.add_observer(earn_bounty).add_systems(Update, apply_laser_damage);
+        line_up_kill(&mut app, BoidKind::Normal, TurretId(1));
+        let turret = app.world_mut().query_filtered::<Entity, With<Turret>>().single(app.world()).unwrap();
+        
+        step(&mut app, 0.1);
+        
+        let stats = app.world().get::<TurretStats>(turret).unwrap();
+        assert_eq!((stats.kills, stats.credits_earned), (1, 5));
+        assert_eq!(app.world().resource::<Currency>().0, Currency::default().0 + 5);
+    }
+    
+    #[test]
+    fn each_integrator_moves_a_constantly_accelerating_boid_as_expected() {
+        let (start, acceleration, dt) = (Vec2::new(10.0, 0.0), Vec2::new(2.0, 0.0), 0.5);
+        let end = start + acceleration * dt;
+        let step = |integrator: Integrator| integrator.integrate_position(Vec2::ZERO, start, end, acceleration, dt).x;
+        
+        assert_eq!(step(Integrator::ExplicitEuler), 5.0);      // 10 * 0.5
+        assert_eq!(step(Integrator::SemiImplicitEuler), 5.5);  // 11 * 0.5
+        assert_eq!(step(Integrator::Verlet), 5.25);            // 10 * 0.5 + 0.5 * 2 * 0.5²
+        assert_eq!(Integrator::default(), Integrator::SemiImplicitEuler);
+    }
+    
+    #[test]
+    fn artillery_holds_fire_until_a_cluster_reaches_the_threshold() {
+        let mut app = sim_app();
+        app.add_systems(Update, fire_artillery);
+        let artillery = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Artillery {
+                    range: 350.0,
+                    density_radius: 60.0,
+                    density_threshold: 4,
+                    splash_radius: 60.0,
+                    splash_damage: 0.1,  // Nobody dies, so the cluster stays put
+                    reload_timer: Timer::from_seconds(0.1, TimerMode::Once),
+                },
+                TurretId(1),
+                TurretStats::default(),
+            ))
+            .id();
+        let shots = |app: &App| app.world().get::<TurretStats>(artillery).unwrap().shots_fired;
+        
+        // Four boids in range but spread out
+        for i in 0..4 {
+            spawn_boid(&mut app, BoidKind::Normal, Vec2::from_angle(i as f32 * 1.5) * 200.0, Vec2::ZERO);
+        }
+        step(&mut app, 0.1);
+        assert_eq!(shots(&app), 0);
+        
+        // Three more gathering around one of them make a cluster of four
+        for i in 0..3 {
+            spawn_boid(&mut app, BoidKind::Normal, Vec2::new(200.0, 10.0 + i as f32 * 10.0), Vec2::ZERO);
+        }
+        step(&mut app, 0.1);
+        assert_eq!(shots(&app), 1);
+    }
+    
+    #[test]
+    fn artillery_kill_fires_boid_killed_like_the_other_weapons() {
+        let mut app = sim_app();
+        app.init_resource::<Despawns>()
+            .init_resource::<Kills>()
+            .add_observer(record_despawn)
+            .add_observer(record_kill)
+            .add_systems(Update, fire_artillery);
+        app.world_mut().spawn((
+            Transform::default(),
+            Artillery {
+                range: 350.0,
+                density_radius: 60.0,
+                density_threshold: 1,
+                splash_radius: 60.0,
+                splash_damage: 10.0,  // Kills outright
+                reload_timer: Timer::from_seconds(0.1, TimerMode::Once),
+            },
+            TurretId(3),
+            TurretStats::default(),
+        ));
+        let boid = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(200.0, 0.0), Vec2::ZERO);
+        let boid_id = *app.world().get::<BoidId>(boid).unwrap();
+        
+        step(&mut app, 0.1);
+        
+        assert_eq!(
+            app.world().resource::<Despawns>().0,
+            [(boid_id, BoidKind::Normal, DespawnCause::KilledByTurret(TurretId(3)))]
+        );
+        assert_eq!(app.world().resource::<Kills>().0, [Vec2::new(200.0, 0.0)]);
+        assert_eq!(app.world().resource::<Score>().points, Score::kill_value(BoidKind::Normal));
+    }
+    
+    #[test]
+    fn draw_boids_reuses_the_shared_mesh_every_frame() {
+        let mut app = visual_app();
+        app.init_resource::<RenderStyle>().add_systems(Update, draw_boids);
+        for i in 0..5 {
+            spawn_boid(&mut app, BoidKind::Normal, Vec2::new(i as f32 * 30.0, 0.0), Vec2::new(100.0, 0.0));
+        }
+        let shared_meshes = app.world().resource::<Assets<Mesh>>().len();
+        
+        step(&mut app, 0.1);  // Attaches the visuals
+        let materials = app.world().resource::<Assets<ColorMaterial>>().len();
+        for _ in 0..10 {
+            step(&mut app, 0.1);
+        }
+        
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), shared_meshes);
+        assert_eq!(app.world().resource::<Assets<ColorMaterial>>().len(), materials);
+    }
+    
+    #[test]
+    fn predator_leader_turret_zone_and_blast_visuals_share_materials() {
+        let mut app = visual_app();
+        app.init_resource::<RenderStyle>()
+            .add_observer(spawn_blast)
+            .add_systems(Update, (draw_boids, attach_turret_visuals, tint_hot_barrels.after(attach_turret_visuals), update_blasts));
+        let material_count = |app: &App| app.world().resource::<Assets<ColorMaterial>>().len();
+        let before = material_count(&app);
+        
+        app.world_mut().spawn((
+            Predator { velocity: Vec2::ZERO, speed: 220.0, catch_radius: 15.0 },
+            Transform::default(),
+            Visibility::default(),
+        ));
+        app.world_mut().spawn((Leader::default(), Transform::default(), Visibility::default()));
+        let turret = spawn_ready_turret(&mut app, Vec2::new(200.0, 0.0), TurretId(1), TargetingMode::Closest, 1, None);
+        app.world_mut().get_mut::<Turret>(turret).unwrap().heat = Turret::OVERHEAT;
+        app.world_mut().run_system_once(setup_protect_zone).unwrap();
+        app.world_mut().trigger(ArtilleryImpact { position: Vec2::ZERO, radius: 50.0 });
+        for _ in 0..3 {
+            step(&mut app, 0.05);
+        }
+        
+        assert_eq!(material_count(&app), before);
+        
+        // Heat and fading still show, by switching between shared shades
+        let assets = app.world().resource::<VisualAssets>();
+        let (hottest, brightest_blast) = (assets.barrel_heat_materials.last().cloned(), assets.blast_materials.last().cloned());
+        let world = app.world_mut();
+        let barrel = world.query_filtered::<&MeshMaterial2d<ColorMaterial>, With<TurretBarrel>>().single(world).unwrap();
+        assert_eq!(Some(barrel.0.clone()), hottest);
+        let blast = world.query_filtered::<&MeshMaterial2d<ColorMaterial>, With<Blast>>().single(world).unwrap();
+        assert_ne!(Some(blast.0.clone()), brightest_blast);
+    }
+    
+    #[test]
+    fn smooth_damage_flash_blends_base_and_red() {
+        let base = Color::srgb(0.2, 0.6, 1.0);
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        let progress = 0.025;  // Sine at 45 degrees: most of the way to red
+        
+        let smooth = damage_flash_color(base, progress, true).to_srgba();
+        let blend = base.mix(&red, std::f32::consts::FRAC_1_SQRT_2).to_srgba();
+        assert!((smooth.red - blend.red).abs() < 1e-4 && (smooth.blue - blend.blue).abs() < 1e-4, "{smooth:?}");
+        assert!(smooth.red > 0.2 && smooth.red < 1.0 && smooth.blue > 0.0 && smooth.blue < 1.0);
+        
+        // The hard toggle only ever shows one or the other
+        assert_eq!(damage_flash_color(base, progress, false), red);
+        assert_eq!(damage_flash_color(base, 0.0, false), base);
+    }
+    
+    #[test]
+    fn headless_run_keeps_boid_and_turret_counts_sane() {
+        let mut app = App::new();
+        add_headless_mode(&mut app);
+        add_simulation(&mut app, true, GameRng::DEFAULT_SEED);
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)));
+        
+        for _ in 0..600 {
+            app.update();  // Ten simulated seconds
+        }
+        
+        let world = app.world_mut();
+        assert_eq!(*world.resource::<State<GameState>>().get(), GameState::Running);
+        let turrets: Vec<u32> = world.query::<&TurretStats>().iter(world).map(|stats| stats.shots_fired).collect();
+        assert_eq!(turrets.len(), 6);  // Five lasers and the artillery
+        assert!(turrets.iter().sum::<u32>() > 0, "turrets never fired");
+        
+        let half_extents = world.resource::<WorldBounds>().half_extents();
+        let first_wave = world.resource::<BoidSettings>().target_count;
+        let boids: Vec<Vec2> = world
+            .query_filtered::<&Transform, With<Boid>>()
+            .iter(world)
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        assert!(!boids.is_empty() && boids.len() <= first_wave * 2, "{} boids", boids.len());
+        assert!(boids.iter().all(|pos| pos.is_finite() && pos.abs().cmple(half_extents + 1.0).all()));
+    }
+    
+    #[test]
+    fn damage_ramps_up_to_full_after_acquiring_a_target() {
+        let mut app = sim_app();
+        app.add_systems(Update, (update_turrets, apply_laser_damage).chain());
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+        spawn_boid(&mut app, BoidKind::Tank, Vec2::new(0.0, 100.0), Vec2::ZERO);  // Straight down the barrel
+        index_boids(&mut app);
+        let full_shot = app.world().get::<Turret>(turret).unwrap().shot_damage;
+        
+        // Damage of each shot, one per reload
+        let mut dealt = 0.0;
+        let mut shots = Vec::new();
+        for seconds in [0.1, 0.5, 0.5] {
+            step(&mut app, seconds);
+            let total = app.world().get::<TurretStats>(turret).unwrap().damage_dealt;
+            shots.push(total - dealt);
+            dealt = total;
+        }
+        
+        assert!(shots[0] < full_shot * 0.5, "first shot after acquiring hit for {}", shots[0]);
+        assert!(shots[0] < shots[1] && shots[1] < full_shot);
+        assert!((shots[2] - full_shot).abs() < 1e-5, "spun-up shot hit for {}", shots[2]);
+    }
+    
+    #[test]
+    fn boids_only_flock_with_their_own_team() {
+        let settings = BoidSettings::default();
+        let steer = |neighbor: Neighbor| {
+            compute_boid_acceleration(Vec2::ZERO, Vec2::new(100.0, 0.0), 300.0, &[neighbor], None, &settings)
+        };
+        // Ahead, outside separation and rival repulsion range, flying across our path
+        let ahead = Neighbor { position: Vec2::new(80.0, 0.0), velocity: Vec2::new(0.0, 100.0), rival: false, cohere: true };
+        
+        let teammate = steer(ahead);
+        assert!(teammate.x > 0.0 && teammate.y > 0.0, "should cohere and align with a teammate: {teammate}");
+        assert_eq!(steer(Neighbor { rival: true, ..ahead }), Vec2::ZERO);
+        
+        // Up close a rival is only pushed away
+        let close_rival = steer(Neighbor { position: Vec2::new(30.0, 0.0), rival: true, ..ahead });
+        assert!(close_rival.x < 0.0 && close_rival.y == 0.0, "{close_rival}");
+    }
+    
+    #[test]
+    fn four_by_three_window_letterboxes_to_sixteen_by_nine() {
+        let mut app = sim_app();
+        app.init_resource::<AspectLock>().add_systems(Update, apply_letterbox);
+        let window = Window { resolution: (1024.0, 768.0).into(), ..default() };
+        assert_eq!(AspectLock::default().playfield(&window), Vec2::new(1024.0, 576.0));
+        app.world_mut().spawn((window, PrimaryWindow));
+        let camera = app.world_mut().spawn(Camera2d).id();
+        
+        step(&mut app, 0.1);
+        
+        // 96px bars above and below: (768 - 576) / 2
+        let viewport = app.world().get::<Camera>(camera).unwrap().viewport.clone().unwrap();
+        assert_eq!(viewport.physical_size, UVec2::new(1024, 576));
+        assert_eq!(viewport.physical_position, UVec2::new(0, 96));
+    }
+    
+    #[test]
+    fn screenshot_is_named_by_seed_and_frame_and_saved_with_notes() {
+        let stem = screenshot_stem(1_700_000_000, 7, 42);
+        assert_eq!(stem, "screenshot-1700000000-seed7-frame42");
+        
+        // Stand-in for a captured frame, since tests have no window to grab
+        let image = Image::new_fill(
+            bevy::render::render_resource::Extent3d { width: 4, height: 4, depth_or_array_layers: 1 },
+            bevy::render::render_resource::TextureDimension::D2,
+            &[255, 0, 0, 255],
+            bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb,
+            bevy::asset::RenderAssetUsages::default(),
+        );
+        let directory = std::env::temp_dir().join(format!("boids-screenshot-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let image_path = directory.join(format!("{stem}.png"));
+        let notes_path = directory.join(format!("{stem}.txt"));
+        
+        save_screenshot(image, image_path.to_str().unwrap(), notes_path.to_str().unwrap(), "seed: 7\n");
+        
+        let png = std::fs::read(&image_path).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(std::fs::read_to_string(&notes_path).unwrap(), "seed: 7\n");
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+    
+    #[test]
+    fn laser_resistant_boid_takes_reduced_laser_but_full_kinetic_damage() {
+        let mut app = sim_app();
+        app.world_mut().resource_mut::<Resistances>().table.insert((DamageType::Laser, BoidKind::Tank), 0.5);
+        app.add_systems(Update, (apply_laser_damage, update_projectiles));
+        
+        // A laser shot straight after acquiring (20% spin-up), halved by the resistance
+        let lasered = spawn_boid(&mut app, BoidKind::Tank, Vec2::new(0.0, 100.0), Vec2::ZERO);
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+        lock_on(&mut app, turret, &[lasered]);
+        let shot = app.world().get::<Turret>(turret).unwrap().shot_damage;
+        
+        // A kinetic round already touching a second tank
+        let shelled = spawn_boid(&mut app, BoidKind::Tank, Vec2::new(500.0, 500.0), Vec2::ZERO);
+        app.world_mut().spawn((
+            Projectile {
+                velocity: Vec2::ZERO,
+                damage: 0.3,
+                target: shelled,
+                turret: Entity::PLACEHOLDER,
+                turret_id: TurretId(2),
+                lifetime: Timer::from_seconds(2.0, TimerMode::Once),
+            },
+            Transform::from_xyz(500.0, 500.0, 0.3),
+        ));
+        app.world_mut()
+            .resource_mut::<SpatialGrid>()
+            .rebuild([(Vec2::new(500.0, 500.0), Vec2::ZERO, shelled, 0, BoidKind::Tank)].into_iter());
+        
+        step(&mut app, 0.1);
+        
+        let health_lost = |app: &App, boid: Entity| {
+            let boid = app.world().get::<Boid>(boid).unwrap();
+            boid.max_health - boid.health
+        };
+        assert!((health_lost(&app, lasered) - shot * 0.2 * 0.5).abs() < 1e-5);
+        assert!((health_lost(&app, shelled) - 0.3).abs() < 1e-5);
+    }
+    
+    /// Press a key for exactly one frame
+    fn press(app: &mut App, key: KeyCode) {
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+        step(app, 0.1);
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(key);
+        keyboard.clear();
+    }
+    
+    /// Turrets at these spots, plus a batch-command app to drive them
+    fn batch_app(positions: &[Vec2]) -> (App, Vec<Entity>) {
+        let mut app = visual_app();
+        app.init_resource::<ButtonInput<KeyCode>>().add_systems(Update, batch_turret_commands);
+        let turrets = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| spawn_ready_turret(&mut app, pos, TurretId(i as u32 + 1), TargetingMode::Closest, 1, None))
+            .collect();
+        (app, turrets)
+    }
+    
+    #[test]
+    fn box_selection_picks_covered_turrets_and_batch_mode_change_applies_to_them() {
+        let positions = [Vec2::ZERO, Vec2::new(100.0, 50.0), Vec2::new(400.0, 0.0)];
+        let (mut app, turrets) = batch_app(&positions);
+        
+        // Dragged up and to the right over the first two
+        let (start, end) = (Vec2::new(-20.0, -20.0), Vec2::new(150.0, 80.0));
+        for (&turret, &pos) in turrets.iter().zip(&positions) {
+            if selection_hit(start, end, pos) {
+                app.world_mut().entity_mut(turret).insert(Selected);
+            }
+        }
+        let selected: Vec<bool> = turrets.iter().map(|&turret| app.world().entity(turret).contains::<Selected>()).collect();
+        assert_eq!(selected, [true, true, false]);
+        
+        press(&mut app, KeyCode::KeyT);
+        
+        let modes: Vec<TargetingMode> = turrets.iter().map(|&turret| app.world().get::<Turret>(turret).unwrap().targeting).collect();
+        assert_eq!(modes, [TargetingMode::Threat, TargetingMode::Threat, TargetingMode::Closest]);
+    }
+    
+    #[test]
+    fn selling_refunds_half_of_what_the_turrets_cost() {
+        let (mut app, turrets) = batch_app(&[Vec2::ZERO, Vec2::new(100.0, 0.0)]);
+        app.world_mut().get_mut::<Turret>(turrets[1]).unwrap().upgrade();  // 40 credits spent on top
+        for &turret in &turrets {
+            app.world_mut().entity_mut(turret).insert(Selected);
+        }
+        let before = app.world().resource::<Currency>().0;
+        
+        press(&mut app, KeyCode::Delete);
+        
+        assert_eq!(app.world().resource::<Currency>().0, before + TURRET_COST / 2 + (TURRET_COST + 40) / 2);
+        assert!(turrets.iter().all(|&turret| app.world().get_entity(turret).is_err()));
+    }
+    
+    #[test]
+    fn leader_anchor_travels_its_waypoints_at_speed_and_loops() {
+        let mut app = sim_app();
+        app.add_systems(Update, advance_leader_path);
+        let start = {
+            let mut path = app.world_mut().resource_mut::<LeaderPath>();
+            path.enabled = true;
+            path.anchor
+        };
+        let anchor = |app: &App| app.world().resource::<LeaderPath>().anchor;
+        let speed = app.world().resource::<LeaderPath>().speed;
+        
+        step(&mut app, 1.0);
+        assert!(anchor(&app).abs_diff_eq(start + Vec2::new(speed, 0.0), 1e-3), "{}", anchor(&app));
+        
+        // The default route is a 1200 x 600 rectangle: 3600 px all the way round
+        step(&mut app, 3600.0 / speed - 1.0);
+        assert!(anchor(&app).abs_diff_eq(start, 1e-2), "{}", anchor(&app));
+        assert_eq!(app.world().resource::<LeaderPath>().next_waypoint, 1);
+    }
+    
+    #[test]
+    fn resizing_boids_rebuilds_the_shared_triangle_to_the_new_extents() {
+        let mut app = visual_app();
+        app.add_systems(Update, rebuild_boid_mesh);
+        step(&mut app, 0.1);
+        {
+            let mut visual_settings = app.world_mut().resource_mut::<VisualSettings>();
+            visual_settings.boid_length = 16.0;
+            visual_settings.boid_width = 10.0;
+        }
+        
+        step(&mut app, 0.1);
+        
+        let handle = app.world().resource::<VisualAssets>().boid_mesh.clone();
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&handle).unwrap();
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(vertices)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("boid mesh has no positions");
+        };
+        let (min, max) = vertices.iter().fold((Vec2::MAX, Vec2::MIN), |(min, max), &[x, y, _]| {
+            (min.min(Vec2::new(x, y)), max.max(Vec2::new(x, y)))
+        });
+        // 16 long with 5/8 ahead of the origin, 10 wide
+        assert_eq!((min, max), (Vec2::new(-5.0, -6.0), Vec2::new(5.0, 10.0)));
+    }
+    
+    #[test]
+    fn prefer_approaching_picks_the_incoming_boid_over_an_equidistant_leaving_one() {
+        // Whether the turret goes for the incoming boid first
+        let picks_incoming = |prefer_approaching: bool| {
+            let mut app = sim_app();
+            // Far off to the left, so without the flag the boid flying left ranks first
+            app.world_mut().resource_mut::<ProtectZone>().center = Vec2::new(-5000.0, 0.0);
+            app.add_systems(Update, update_turrets);
+            let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+            app.world_mut().get_mut::<Turret>(turret).unwrap().prefer_approaching = prefer_approaching;
+            spawn_boid(&mut app, BoidKind::Normal, Vec2::new(-100.0, 0.0), Vec2::new(-150.0, 0.0));  // Leaving
+            let incoming = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(100.0, 0.0), Vec2::new(-150.0, 0.0));
+            index_boids(&mut app);
+            
+            step(&mut app, 0.1);
+            
+            app.world().get::<Turret>(turret).unwrap().targets == [incoming]
+        };
+        
+        assert!(!picks_incoming(false));
+        assert!(picks_incoming(true));
+    }
+    
+    #[test]
+    fn sprinting_drains_energy_and_lowers_top_speed() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_boids);
+        let boid = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(-700.0, 0.0), Vec2::new(300.0, 0.0));
+        
+        // Floor it for two seconds
+        for _ in 0..20 {
+            app.world_mut().get_mut::<Boid>(boid).unwrap().acceleration = Vec2::new(5000.0, 0.0);
+            step(&mut app, 0.1);
+        }
+        
+        let boid = app.world().get::<Boid>(boid).unwrap();
+        assert!(boid.energy < 0.6, "energy {}", boid.energy);
+        let stamina = Stamina::default();
+        let top_speed = stamina.max_speed(boid.max_speed, boid.energy);
+        assert!(top_speed < boid.max_speed * 0.9);
+        // Capped by the energy left at the start of the last tick
+        let energy_before = boid.energy + stamina.drain_rate * 0.1;
+        assert!(boid.velocity.length() <= stamina.max_speed(boid.max_speed, energy_before) + 1e-3);
+    }
+    
+    #[test]
+    fn gravity_well_bends_passing_boids_with_a_bounded_pull() {
+        // Velocity change over one tick for a boid at `position` flying right past a well at the origin
+        let nudge = |position: Vec2| {
+            let mut app = sim_app();
+            app.add_systems(Update, update_boids);
+            app.world_mut().spawn((
+                GravityWell { strength: 4_000_000.0, core_radius: 30.0 },
+                Transform::default(),
+            ));
+            let heading = Vec2::new(200.0, 0.0);
+            let boid = spawn_boid(&mut app, BoidKind::Normal, position, heading);
+            
+            step(&mut app, 0.1);
+            
+            app.world().get::<Boid>(boid).unwrap().velocity - heading * 0.99  // Minus damping
+        };
+        let max_pull = BoidSettings::default().max_force * 0.1;
+        
+        // Passing above the well swerves down towards it
+        let passing = nudge(Vec2::new(-50.0, 150.0));
+        assert!(passing.y < 0.0, "{passing}");
+        
+        // Skimming the center is no harder than max force, however close
+        let skimming = nudge(Vec2::new(0.0, 1.0));
+        assert!(skimming.y < 0.0 && skimming.length() <= max_pull + 1e-3, "{skimming}");
+    }
+    
+    #[test]
+    fn loaded_settings_are_clamped_like_the_settings_panel() {
+        let mut loaded: BoidSettings =
+            ron::from_str("(perception_radius: 1e9, max_speed: -50.0, target_count: 100000)").unwrap();
+        
+        loaded.sanitize();
+        
+        assert_eq!(loaded.perception_radius, SettingField::PerceptionRadius.range().1);
+        assert_eq!(loaded.max_speed, SettingField::MaxSpeed.range().0);
+        assert_eq!(loaded.target_count, 1000);
+        
+        // Values nothing could step to fall back to the defaults
+        let mut corrupt = BoidSettings { perception_radius: f32::NAN, ..default() };
+        corrupt.sanitize();
+        assert_eq!(corrupt.perception_radius, BoidSettings::default().perception_radius);
+    }
+    
+    #[test]
+    fn new_laser_flash_spans_muzzle_to_target_on_its_first_frame() {
+        let mut app = visual_app();
+        app.init_resource::<LaserSettings>()
+            .add_observer(spawn_laser_flash)
+            .add_systems(Update, (
+                update_boids,
+                apply_laser_damage,
+                update_lasers.after(apply_laser_damage).after(update_boids),  // As in `main`
+            ));
+        let boid = spawn_boid(&mut app, BoidKind::Tank, Vec2::new(0.0, 100.0), Vec2::new(200.0, 0.0));
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::Closest, 1, None);
+        lock_on(&mut app, turret, &[boid]);
+        
+        step(&mut app, 1.0 / 60.0);  // One real frame; a 0.1 s step would outlast the flash itself
+        
+        let target = app.world().get::<Transform>(boid).unwrap().translation.truncate();
+        let muzzle = app.world().get::<Turret>(turret).unwrap().muzzle(Vec2::ZERO);
+        let beam = *app.world_mut().query_filtered::<&Transform, With<LaserBeam>>().single(app.world()).unwrap();
+        assert!(target.x > 0.0, "the boid should have moved this frame");
+        assert!((beam.scale.y - muzzle.distance(target)).abs() < 1e-3, "beam {} long", beam.scale.y);
+        assert!(beam.translation.truncate().abs_diff_eq((muzzle + target) / 2.0, 1e-3));
+    }
+    
+    #[test]
+    fn reset_clears_wells_heatmap_and_trails_and_calms_a_panic() {
+        let mut app = sim_app();
+        spawn_boid(&mut app, BoidKind::Normal, Vec2::ZERO, Vec2::ZERO);
+        let wells_and_effects = [
+            app.world_mut().spawn(GravityWell { strength: 1.0, core_radius: 30.0 }).id(),
+            app.world_mut().spawn(HeatmapCell).id(),
+            app.world_mut().spawn(Trail { lifetime: Timer::from_seconds(1.0, TimerMode::Once) }).id(),
+        ];
+        let bystander = app.world_mut().spawn(Transform::default()).id();
+        app.world_mut().resource_mut::<PanicTimer>().timer.reset();  // Mid-panic
+        
+        app.world_mut().run_system_once(reset_game).unwrap();
+        
+        assert!(wells_and_effects.iter().all(|&entity| app.world().get_entity(entity).is_err()));
+        assert_eq!(app.world_mut().query::<&Boid>().iter(app.world()).count(), 0);
+        assert!(app.world().get_entity(bystander).is_ok());
+        assert_eq!(app.world().resource::<PanicTimer>().current_strength(), 0.0);
+    }
+    
+    #[test]
+    fn lowest_health_turret_passes_over_a_closer_healthy_boid_for_a_wounded_one() {
+        let mut app = sim_app();
+        app.add_systems(Update, update_turrets);
+        let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), TargetingMode::LowestHealth, 1, None);
+        spawn_boid(&mut app, BoidKind::Normal, Vec2::new(0.0, 60.0), Vec2::ZERO);
+        let wounded = spawn_boid(&mut app, BoidKind::Normal, Vec2::new(0.0, -200.0), Vec2::ZERO);
+        app.world_mut().get_mut::<Boid>(wounded).unwrap().health *= 0.25;
+        index_boids(&mut app);
+        
+        step(&mut app, 0.1);
+        
+        assert_eq!(app.world().get::<Turret>(turret).unwrap().targets, [wounded]);
+    }
+    
+    #[test]
+    fn grid_acquisition_picks_the_same_targets_as_a_full_scan() {
+        let acquire = |mode: TargetingMode, cell_size: f32| {
+            let mut app = sim_app();
+            app.world_mut().resource_mut::<SpatialGrid>().cell_size = cell_size;
+            app.world_mut().resource_mut::<ProtectZone>().center = Vec2::new(0.0, -300.0);
+            app.add_systems(Update, update_turrets);
+            let turret = spawn_ready_turret(&mut app, Vec2::ZERO, TurretId(1), mode, 3, None);
+            spawn_ready_turret(&mut app, Vec2::new(150.0, 100.0), TurretId(2), mode, 1, None);  // For Threat
+            let mut rng = StdRng::seed_from_u64(7);
+            for _ in 0..80 {
+                let kind = BoidKind::random(&mut rng);
+                let position = Vec2::new(rng.random_range(-400.0..400.0), rng.random_range(-400.0..400.0));
+                let velocity = Vec2::new(rng.random_range(-150.0..150.0), rng.random_range(-150.0..150.0));
+                let boid = spawn_boid(&mut app, kind, position, velocity);
+                app.world_mut().get_mut::<Boid>(boid).unwrap().health *= rng.random_range(0.1..1.0);
+            }
+            index_boids(&mut app);
+            
+            step(&mut app, 0.1);
+            
+            app.world().get::<Turret>(turret).unwrap().targets.clone()
+        };
+        
+        // One cell wider than the whole flock puts every boid in every query: a full scan
+        for mode in TargetingMode::ALL {
+            let scanned = acquire(mode, 1e6);
+            assert_eq!(scanned.len(), 3, "{mode:?}");
+            assert_eq!(acquire(mode, SpatialGrid::default().cell_size), scanned, "{mode:?}");
+        }
+    }
+    
+    #[test]
+    fn boid_driven_into_a_corner_slides_out_instead_of_stalling() {
+        let half_extents = Vec2::new(600.0, 400.0);
+        let corner = half_extents;
+        let settings = BoidSettings::default();
+        // Something past the corner (a target, the flock) keeps pulling the boid into it
+        let drive = Vec2::splat(1.0).normalize() * settings.max_force;
+        let mut position = corner - Vec2::splat(40.0);
+        let mut velocity = Vec2::ZERO;
+        let dt = 1.0 / 60.0;
+        
+        for _ in 0..60 {
+            let steering = drive + edge_avoidance(position, velocity, half_extents, EDGE_MARGIN, EDGE_FORCE);
+            velocity = (velocity + steering * dt).clamp_length_max(settings.max_speed) * 0.99;
+            position += velocity * dt;
+            assert!(position.x < half_extents.x && position.y < half_extents.y, "hit the wall at {position}");
+        }
+        
+        assert!(position.distance(corner) > 150.0, "still wedged at {position}");
+    }
+    
+    #[test]
+    fn sweep_writes_one_row_per_weight_combination() {
+        let csv = run_sweep(&[0.2, 1.0], &[0.5, 1.0, 1.5], 30, GameRng::DEFAULT_SEED);
+        
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("cohesion_weight,separation_weight,clusters,boids,avg_speed,dispersion"));
+        let combinations: Vec<(f32, f32)> = lines
+            .map(|row| {
+                let fields: Vec<f32> = row.split(',').map(|field| field.parse().unwrap()).collect();
+                assert_eq!(fields.len(), 6, "{row}");
+                assert!(fields[3] > 0.0, "no boids left in {row}");
+                (fields[0], fields[1])
+            })
+            .collect();
+        assert_eq!(combinations, [(0.2, 0.5), (0.2, 1.0), (0.2, 1.5), (1.0, 0.5), (1.0, 1.0), (1.0, 1.5)]);
+    }
+    
+    #[test]
+    fn placing_a_turret_moves_the_tutorial_on_to_selecting_one() {
+        let mut app = sim_app();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<BuildMode>()
+            .insert_resource(Tutorial { step: Some(TutorialStep::PlaceTurret), finished: false })
+            .add_systems(Update, (advance_tutorial, update_tutorial_panel).chain());
+        app.world_mut().resource_mut::<BuildMode>().active = true;
+        let current = |app: &App| app.world().resource::<Tutorial>().step;
+        
+        // Sitting in build mode, or doing a later step first, doesn't count
+        app.world_mut().resource_mut::<Tutorial>().complete(TutorialStep::SelectTurret);
+        step(&mut app, 0.1);
+        assert_eq!(current(&app), Some(TutorialStep::PlaceTurret));
+        
+        // What `place_turret` does once the turret is paid for
+        app.world_mut().resource_mut::<Tutorial>().complete(TutorialStep::PlaceTurret);
+        step(&mut app, 0.1);
+        
+        assert_eq!(current(&app), Some(TutorialStep::SelectTurret));
+        let world = app.world_mut();
+        assert_eq!(world.query_filtered::<(), With<TutorialPanel>>().iter(world).count(), 1);
+        assert!(!world.resource::<BoidSettings>().tutorial_done);
+    }
+    
+    #[test]
+    fn locked_turret_kind_cannot_be_built_until_its_wave_is_reached() {
+        let mut app = sim_app();
+        app.add_systems(Update, unlock_turret_kinds);
+        let starting_layout = 6;
+        let blocker = |app: &mut App, kind: TurretKind, placed: usize| {
+            app.world_mut().resource_mut::<TechProgress>().selected = kind;
+            app.world().resource::<TechProgress>().build_blocker(app.world().resource::<BuildLimits>(), placed)
+        };
+        
+        step(&mut app, 0.1);
+        assert_eq!(blocker(&mut app, TurretKind::Launcher, starting_layout).as_deref(), Some("Launcher turrets are still locked"));
+        assert_eq!(blocker(&mut app, TurretKind::Laser, starting_layout), None);
+        
+        app.world_mut().resource_mut::<WaveState>().wave = 2;
+        step(&mut app, 0.1);
+        
+        assert_eq!(blocker(&mut app, TurretKind::Launcher, starting_layout), None);
+        assert!(blocker(&mut app, TurretKind::Artillery, starting_layout).is_some(), "artillery waits for wave 3");
+        
+        // Unlocked or not, nothing gets past the cap
+        let cap = app.world().resource::<BuildLimits>().max_turrets;
+        assert_eq!(blocker(&mut app, TurretKind::Laser, cap).as_deref(), Some(format!("Turret limit of {cap} reached").as_str()));
+    }
+    
+    #[test]
+    fn restart_rebuilds_the_opening_board_without_leftovers() {
+        let mut app = App::new();
+        add_headless_mode(&mut app);
+        add_simulation(&mut app, true, GameRng::DEFAULT_SEED);
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)))
+            .init_resource::<KillFeed>()
+            .init_resource::<Recorder>();
+        let board = |app: &mut App| {
+            let world = app.world_mut();
+            let mut boids: Vec<(BoidId, BoidKind)> = world.query::<(&BoidId, &BoidKind)>().iter(world).map(|(id, kind)| (*id, *kind)).collect();
+            boids.sort_by_key(|(id, _)| id.0);
+            (boids, world.query::<&TurretId>().iter(world).count())
+        };
+        
+        app.update();
+        let fresh = board(&mut app);
+        for _ in 0..600 {
+            app.update();  // Ten simulated seconds of shooting
+        }
+        // Effects only windowed runs spawn, plus a kill feed line and a recorded frame
+        let world = app.world_mut();
+        world.spawn(Particle { velocity: Vec2::X, lifetime: Timer::from_seconds(1.0, TimerMode::Once) });
+        world.spawn(Blast { lifetime: Timer::from_seconds(1.0, TimerMode::Once) });
+        let line = world.spawn(KillFeedEntry { lifetime: Timer::from_seconds(5.0, TimerMode::Once) }).id();
+        world.resource_mut::<KillFeed>().entries.push_back(line);
+        world.resource_mut::<Recorder>().frames.push_back((1, Vec::new()));
+        world.resource_mut::<LeaderPath>().next_waypoint = 3;
+        
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Restarting);
+        app.update();  // Into `Restarting`: clear and rebuild
+        app.update();  // Back to `Running`
+        
+        let world = app.world_mut();
+        assert_eq!(*world.resource::<State<GameState>>().get(), GameState::Running);
+        let leftovers = world
+            .query_filtered::<(), Or<(With<LaserBeam>, With<Particle>, With<Projectile>, With<Blast>, With<KillFeedEntry>)>>()
+            .iter(world)
+            .count();
+        assert_eq!(leftovers, 0);
+        assert!(world.resource::<KillFeed>().entries.is_empty());
+        assert!(world.resource::<Recorder>().frames.is_empty());
+        assert_eq!(world.resource::<LeaderPath>().next_waypoint, 1);
+        // Same ids and kinds, so the same boids lead
+        assert_eq!(board(&mut app), fresh);
+    }
+}